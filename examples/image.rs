@@ -3,11 +3,11 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::path::Path;
+use std::{path::Path, time::Duration as StdDuration};
 use tauri_winrt_notification::{IconCrop, Toast};
 
 fn main() {
-    Toast::new("application that needs a toast with an image")
+    let handle = Toast::new("application that needs a toast with an image")
         .hero(Path::new("C:\\absolute\\path\\to\\image.jpeg"), "alt text")
         .icon(
             Path::new("c:/this/style/works/too/image.png"),
@@ -22,4 +22,9 @@ fn main() {
         .sound(None) // will be silent
         .show()
         .expect("notification failed");
+
+    // keep the process alive until windows picked up the toast
+    handle
+        .wait_delivered(StdDuration::from_secs(1))
+        .expect("notification failed");
 }
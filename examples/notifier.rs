@@ -0,0 +1,30 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration as StdDuration;
+
+use tauri_winrt_notification::{Notifier, Toast};
+
+fn main() {
+    // create the notifier once and reuse it for every toast
+    let notifier = Notifier::new(Toast::POWERSHELL_APP_ID).expect("notifier unavailable");
+
+    for i in 1..=3 {
+        // the toast is pure content, the notifier decides which app it is shown for
+        let toast = Toast::default()
+            .title(&format!("toast #{i}"))
+            .text1("sent through a shared notifier")
+            .tag(&format!("toast-{i}"));
+        let handle = notifier.show(&toast).expect("notification failed");
+        handle
+            .wait_delivered(StdDuration::from_secs(1))
+            .expect("notification failed");
+    }
+
+    let history = notifier.history().expect("history unavailable");
+    for entry in history.entries().expect("failed to read history") {
+        println!("in action center: {}", entry.tag);
+    }
+}
@@ -20,7 +20,11 @@ fn main() {
         .text1("Transferring files to your computer...")
         .progress(&progress)
         .duration(Duration::Long);
-    toast.show().expect("notification failed");
+    let handle = toast.show().expect("notification failed");
+    // updates only reach a toast that was delivered
+    handle
+        .wait_delivered(StdDuration::from_secs(1))
+        .expect("notification failed");
 
     for i in 1..=10 {
         sleep(StdDuration::from_secs(1));
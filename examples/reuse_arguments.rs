@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use std::time::Duration as StdDuration;
+
 use tauri_winrt_notification::{Duration, Sound, Toast};
 
 fn main() {
@@ -13,12 +15,12 @@ fn main() {
         .title("first toast")
         .text1("line1")
         .duration(duration)
-        .sound(sound)
+        .sound(sound.clone())
         .show()
         // silently consume errors
         .expect("notification failed");
 
-    Toast::new(Toast::POWERSHELL_APP_ID)
+    let handle = Toast::new(Toast::POWERSHELL_APP_ID)
         .title("another toast")
         .text1("line1")
         .duration(duration)
@@ -26,4 +28,9 @@ fn main() {
         .show()
         // silently consume errors
         .expect("notification failed");
+
+    // keep the process alive until windows picked up the toasts
+    handle
+        .wait_delivered(StdDuration::from_secs(1))
+        .expect("notification failed");
 }
@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use std::time::Duration as StdDuration;
+
 use tauri_winrt_notification::{Duration, Sound, Toast};
 
 fn main() {
@@ -20,8 +22,13 @@ fn main() {
         // silently consume errors
         .expect("notification failed");
 
-    toast
+    let handle = toast
         .show()
         // silently consume errors
         .expect("notification failed");
+
+    // keep the process alive until windows picked up the toasts
+    handle
+        .wait_delivered(StdDuration::from_secs(1))
+        .expect("notification failed");
 }
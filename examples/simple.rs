@@ -8,7 +8,8 @@ use std::{process::exit, thread::sleep, time::Duration as StdDuration};
 use tauri_winrt_notification::{Duration, Sound, Toast, ToastDismissalReason};
 
 fn main() {
-    Toast::new(Toast::POWERSHELL_APP_ID)
+    // the handlers are only called as long as the handle is kept
+    let _handle = Toast::new(Toast::POWERSHELL_APP_ID)
         .title("Look at this flip!")
         .text1("(╯°□°）╯︵ ┻━┻")
         .sound(Some(Sound::SMS))
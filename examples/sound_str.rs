@@ -3,14 +3,21 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use std::time::Duration as StdDuration;
+
 use tauri_winrt_notification::{Duration, Toast};
 
 fn main() {
-    Toast::new(Toast::POWERSHELL_APP_ID)
+    let handle = Toast::new(Toast::POWERSHELL_APP_ID)
         .title("Look at this flip!")
         .text1("(╯°□°）╯︵ ┻━┻")
         .sound("Alarm5".try_into().ok())
         .duration(Duration::Short)
         .show()
         .expect("unable to send notification");
+
+    // keep the process alive until windows picked up the toast
+    handle
+        .wait_delivered(StdDuration::from_secs(1))
+        .expect("unable to send notification");
 }
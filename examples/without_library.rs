@@ -4,19 +4,28 @@
 // SPDX-License-Identifier: MIT
 
 // How to create a toast without using this library
+#[cfg(windows)]
 use std::path::Path;
 
 // You need to have the windows crate in your Cargo.toml
 // with the following features:
 //    "Data_Xml_Dom"
 //    "UI_Notifications"
+#[cfg(windows)]
 use windows::{
     Data::Xml::Dom::XmlDocument, UI::Notifications::ToastNotification,
     UI::Notifications::ToastNotificationManager,
 };
 
+#[cfg(windows)]
 pub use windows::core::{Error, HSTRING};
 
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("toasts are only available on windows");
+}
+
+#[cfg(windows)]
 fn main() {
     do_toast().expect("not sure if this is actually failable");
     // this is a hack to workaround toasts not showing up if the application closes too quickly
@@ -24,6 +33,7 @@ fn main() {
     std::thread::sleep(std::time::Duration::from_millis(10));
 }
 
+#[cfg(windows)]
 fn do_toast() -> windows::core::Result<()> {
     let toast_xml = XmlDocument::new()?;
 
@@ -55,5 +65,6 @@ fn do_toast() -> windows::core::Result<()> {
 
     // Show the toast.
     // Note this returns success in every case, including when the toast isn't shown.
+    // Attach a handler with `toast_template.Failed(...)` to find out about those failures.
     toast_notifier.Show(&toast_template)
 }
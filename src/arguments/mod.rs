@@ -0,0 +1,243 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Key/value arguments in the encoding of the Windows Community Toolkit.
+
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+mod typed;
+
+/// Key/value pairs sent back when a toast is activated, instead of a hand made string.
+///
+/// Encoded like the `ToastArguments` of the Windows Community Toolkit, as in
+/// `action=reply;conversationId=9813`, so toasts can be shared with apps using it.
+/// `%`, `;` and `=` in keys and values are escaped, so any text round-trips.
+/// Keys may also be added without a value.
+///
+/// The encoded arguments can be used as the action of a [button](crate::Toast::add_button)
+/// or the arguments of a [Header](crate::Header), and are decoded again on activation
+/// with [decode](Self::decode) or [Activation::toast_arguments](crate::Activation::toast_arguments).
+///
+/// With the `serde` feature, a struct can be turned into arguments and back,
+/// see [ToastArguments::serialize] and [ToastArguments::deserialize].
+///
+/// # Example
+/// ```rust,no_run
+/// use tauri_winrt_notification::{Toast, ToastArguments};
+///
+/// let reply = ToastArguments::new()
+///     .add("action", "reply")
+///     .add("conversationId", "9813");
+///
+/// Toast::new(Toast::POWERSHELL_APP_ID)
+///     .title("Alice")
+///     .add_button("Reply", &reply.to_string())
+///     .on_activated(|action| {
+///         let arguments = ToastArguments::decode(action.as_deref().unwrap_or_default());
+///         if arguments.get("action") == Some("reply") {
+///             println!("replying to {:?}", arguments.get("conversationId"));
+///         }
+///         Ok(())
+///     })
+///     .show()
+///     .expect("notification failed");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToastArguments {
+    /// Every key once, in the order they were added.
+    pairs: Vec<(String, Option<String>)>,
+}
+
+impl ToastArguments {
+    /// No arguments.
+    pub fn new() -> ToastArguments {
+        ToastArguments::default()
+    }
+
+    /// Set `key` to `value`, replacing any earlier value of `key`.
+    pub fn add(self, key: &str, value: &str) -> ToastArguments {
+        self.insert(key, Some(value.to_owned()))
+    }
+
+    /// Add `key` without a value, like a flag.
+    pub fn add_key(self, key: &str) -> ToastArguments {
+        self.insert(key, None)
+    }
+
+    fn insert(mut self, key: &str, value: Option<String>) -> ToastArguments {
+        match self.pairs.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.pairs.push((key.to_owned(), value)),
+        }
+        self
+    }
+
+    /// The value of `key`, `None` if the key is missing or has no value.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Whether `key` was added, with or without a value.
+    pub fn contains(&self, key: &str) -> bool {
+        self.pairs.iter().any(|(k, _)| k == key)
+    }
+
+    /// Remove `key`, returning whether it was there.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.pairs.len();
+        self.pairs.retain(|(k, _)| k != key);
+        self.pairs.len() != len
+    }
+
+    /// The keys and their values, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.pairs
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_deref()))
+    }
+
+    /// The number of keys.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Whether there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Decode `arguments`.
+    ///
+    /// Never fails, so arguments of toasts not using this encoding decode to keys without values.
+    /// Empty pairs are skipped and the last value of a repeated key wins.
+    pub fn decode(arguments: &str) -> ToastArguments {
+        arguments.split(';').filter(|pair| !pair.is_empty()).fold(
+            ToastArguments::new(),
+            |decoded, pair| match pair.split_once('=') {
+                Some((key, value)) => decoded.insert(&unescape(key), Some(unescape(value))),
+                None => decoded.insert(&unescape(pair), None),
+            },
+        )
+    }
+}
+
+impl Display for ToastArguments {
+    /// The encoded arguments.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (key, value)) in self.pairs.iter().enumerate() {
+            if i > 0 {
+                write!(f, ";")?;
+            }
+            write!(f, "{}", escape(key))?;
+            if let Some(value) = value {
+                write!(f, "={}", escape(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ToastArguments {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ToastArguments::decode(s))
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for ToastArguments {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(ToastArguments::new(), |arguments, (key, value)| {
+                arguments.add(key.as_ref(), value.as_ref())
+            })
+    }
+}
+
+/// The escape sequences of the Community Toolkit, `%` first so it is not escaped twice.
+const ESCAPES: [(char, &str); 3] = [('%', "%25"), (';', "%3B"), ('=', "%3D")];
+
+fn escape(text: &str) -> String {
+    ESCAPES.iter().fold(text.to_owned(), |text, (c, escaped)| {
+        text.replace(*c, escaped)
+    })
+}
+
+fn unescape(text: &str) -> String {
+    ESCAPES
+        .iter()
+        .rev()
+        .fold(text.to_owned(), |text, (c, escaped)| {
+            text.replace(escaped, &c.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_like_the_toolkit() {
+        let arguments = ToastArguments::new()
+            .add("action", "viewConversation")
+            .add("conversationId", "9813")
+            .add_key("markAsRead");
+        assert_eq!(
+            arguments.to_string(),
+            "action=viewConversation;conversationId=9813;markAsRead"
+        );
+        assert_eq!(
+            ToastArguments::decode("action=viewConversation;conversationId=9813;markAsRead"),
+            arguments
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        let arguments = ToastArguments::new()
+            .add("a=b;c", "100%;x=y")
+            .add("empty", "")
+            .add("%3B", "%25");
+        let encoded = arguments.to_string();
+        assert_eq!(encoded, "a%3Db%3Bc=100%25%3Bx%3Dy;empty=;%253B=%2525");
+        let decoded: ToastArguments = encoded.parse().unwrap();
+        assert_eq!(decoded, arguments);
+        assert_eq!(decoded.get("a=b;c"), Some("100%;x=y"));
+        assert_eq!(decoded.get("%3B"), Some("%25"));
+        assert_eq!(decoded.get("empty"), Some(""));
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        let mut arguments = ToastArguments::new()
+            .add("action", "open")
+            .add("id", "1")
+            .add("action", "close");
+        assert_eq!(arguments.to_string(), "action=close;id=1");
+        assert_eq!(
+            ToastArguments::decode("a=1;;a=2;b"),
+            ToastArguments::decode("a=2;b")
+        );
+
+        assert!(arguments.remove("action"));
+        assert!(!arguments.remove("action"));
+        assert_eq!(arguments.len(), 1);
+        assert_eq!(arguments.iter().collect::<Vec<_>>(), [("id", Some("1"))]);
+    }
+
+    #[test]
+    fn decodes_anything() {
+        let arguments = ToastArguments::decode("reply");
+        assert!(arguments.contains("reply"));
+        assert_eq!(arguments.get("reply"), None);
+        assert!(ToastArguments::decode("").is_empty());
+    }
+}
@@ -0,0 +1,620 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Turning flat structs and maps into [ToastArguments] and back with serde.
+//!
+//! Every field becomes a key, with the text of a string, number, bool, char or unit variant
+//! as its value. `None` leaves the key out and `()` adds the key without a value.
+//! Nested structs, sequences and other variants cannot be expressed as arguments.
+
+use std::fmt::Display;
+
+use serde::de::value::{MapDeserializer, StrDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+
+use super::ToastArguments;
+use crate::Error;
+
+impl ToastArguments {
+    /// The fields of `value`, a struct or map of strings, numbers, bools, chars or unit variants.
+    ///
+    /// Fails with [Error::InvalidArguments] for anything else.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde::Serialize;
+    /// use tauri_winrt_notification::ToastArguments;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Reply {
+    ///     action: &'static str,
+    ///     conversation_id: u64,
+    /// }
+    ///
+    /// let arguments = ToastArguments::serialize(&Reply { action: "reply", conversation_id: 9813 }).unwrap();
+    /// assert_eq!(arguments.to_string(), "action=reply;conversation_id=9813");
+    /// ```
+    pub fn serialize<T: Serialize + ?Sized>(value: &T) -> crate::Result<ToastArguments> {
+        value
+            .serialize(ArgumentsSerializer)
+            .map_err(|error| Error::InvalidArguments(error.0))
+    }
+
+    /// Parse the arguments into `T`, the reverse of [serialize](Self::serialize).
+    ///
+    /// Keys without a value read as `true`, `()` or an empty string.
+    /// Fails with [Error::InvalidArguments] if a field is missing or does not parse.
+    pub fn deserialize<T: DeserializeOwned>(&self) -> crate::Result<T> {
+        let pairs = self
+            .pairs
+            .iter()
+            .map(|(key, value)| (key.as_str(), ValueDeserializer(value.as_deref())));
+        T::deserialize(MapDeserializer::new(pairs))
+            .map_err(|error: ArgumentsError| Error::InvalidArguments(error.0))
+    }
+}
+
+/// The error while (de)serializing, turned into [Error::InvalidArguments].
+#[derive(Debug)]
+struct ArgumentsError(String);
+
+impl Display for ArgumentsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ArgumentsError {}
+
+impl ser::Error for ArgumentsError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ArgumentsError(msg.to_string())
+    }
+}
+
+impl de::Error for ArgumentsError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ArgumentsError(msg.to_string())
+    }
+}
+
+fn unsupported(what: &str) -> ArgumentsError {
+    ArgumentsError(format!("{what} cannot be toast arguments"))
+}
+
+/// Serializes the struct or map at the top.
+struct ArgumentsSerializer;
+
+/// Collects the fields of the struct or map at the top.
+struct Fields {
+    arguments: ToastArguments,
+    key: Option<String>,
+}
+
+impl Fields {
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), ArgumentsError> {
+        let arguments = std::mem::take(&mut self.arguments);
+        self.arguments = match value.serialize(ValueSerializer)? {
+            Value::Missing => arguments,
+            Value::Key => arguments.add_key(key),
+            Value::Text(text) => arguments.add(key, &text),
+        };
+        Ok(())
+    }
+}
+
+/// A serialized field.
+enum Value {
+    /// `None`, leaving the key out.
+    Missing,
+    /// `()`, adding the key without a value.
+    Key,
+    Text(String),
+}
+
+macro_rules! unsupported_top {
+    ($($method:ident($($arg:ty),*) => $what:literal,)*) => {
+        $(fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Self::Error> {
+            Err(unsupported($what))
+        })*
+    };
+}
+
+impl ser::Serializer for ArgumentsSerializer {
+    type Ok = ToastArguments;
+    type Error = ArgumentsError;
+    type SerializeSeq = Impossible<ToastArguments, ArgumentsError>;
+    type SerializeTuple = Impossible<ToastArguments, ArgumentsError>;
+    type SerializeTupleStruct = Impossible<ToastArguments, ArgumentsError>;
+    type SerializeTupleVariant = Impossible<ToastArguments, ArgumentsError>;
+    type SerializeMap = Fields;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<ToastArguments, ArgumentsError>;
+
+    unsupported_top! {
+        serialize_bool(bool) => "a bool",
+        serialize_i8(i8) => "a number",
+        serialize_i16(i16) => "a number",
+        serialize_i32(i32) => "a number",
+        serialize_i64(i64) => "a number",
+        serialize_u8(u8) => "a number",
+        serialize_u16(u16) => "a number",
+        serialize_u32(u32) => "a number",
+        serialize_u64(u64) => "a number",
+        serialize_f32(f32) => "a number",
+        serialize_f64(f64) => "a number",
+        serialize_char(char) => "a char",
+        serialize_str(&str) => "a string",
+        serialize_bytes(&[u8]) => "bytes",
+        serialize_none() => "an option",
+        serialize_unit() => "a unit",
+        serialize_unit_struct(&'static str) => "a unit struct",
+        serialize_unit_variant(&'static str, u32, &'static str) => "an enum",
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an option"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an enum"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported("an enum"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Fields {
+            arguments: ToastArguments::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported("an enum"))
+    }
+}
+
+impl ser::SerializeMap for Fields {
+    type Ok = ToastArguments;
+    type Error = ArgumentsError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        match key.serialize(ValueSerializer)? {
+            Value::Text(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            Value::Missing | Value::Key => Err(unsupported("a key without text")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self.key.take().unwrap_or_default();
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.arguments)
+    }
+}
+
+impl ser::SerializeStruct for Fields {
+    type Ok = ToastArguments;
+    type Error = ArgumentsError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.arguments)
+    }
+}
+
+/// Serializes a single field.
+struct ValueSerializer;
+
+macro_rules! display_value {
+    ($($method:ident($ty:ty),)*) => {
+        $(fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+            Ok(Value::Text(value.to_string()))
+        })*
+    };
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = ArgumentsError;
+    type SerializeSeq = Impossible<Value, ArgumentsError>;
+    type SerializeTuple = Impossible<Value, ArgumentsError>;
+    type SerializeTupleStruct = Impossible<Value, ArgumentsError>;
+    type SerializeTupleVariant = Impossible<Value, ArgumentsError>;
+    type SerializeMap = Impossible<Value, ArgumentsError>;
+    type SerializeStruct = Impossible<Value, ArgumentsError>;
+    type SerializeStructVariant = Impossible<Value, ArgumentsError>;
+
+    display_value! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Missing)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Key)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Key)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Text(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(unsupported("a nested map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(unsupported("a nested struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+}
+
+/// Deserializes the value of a key, `None` if the key has no value.
+struct ValueDeserializer<'de>(Option<&'de str>);
+
+impl<'de> IntoDeserializer<'de, ArgumentsError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident,)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            let text = self.0.unwrap_or_default();
+            match text.parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(de::Error::invalid_value(de::Unexpected::Str(text), &visitor)),
+            }
+        })*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = ArgumentsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Some(text) => visitor.visit_borrowed_str(text),
+            None => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            None => visitor.visit_bool(true),
+            Some(text) => match text.parse() {
+                Ok(value) => visitor.visit_bool(value),
+                Err(_) => Err(de::Error::invalid_value(
+                    de::Unexpected::Str(text),
+                    &visitor,
+                )),
+            },
+        }
+    }
+
+    parse_value! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0.unwrap_or_default())
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // a missing key never gets here, serde fills in `None` for it
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let variant: StrDeserializer<ArgumentsError> =
+            self.0.unwrap_or_default().into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    enum Action {
+        Reply,
+        MarkAsRead,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Arguments {
+        action: Action,
+        conversation_id: u64,
+        text: String,
+        urgent: bool,
+        reply_to: Option<u32>,
+    }
+
+    #[test]
+    fn round_trips() {
+        let arguments = Arguments {
+            action: Action::MarkAsRead,
+            conversation_id: 9813,
+            text: "a=b; 100%".to_owned(),
+            urgent: false,
+            reply_to: None,
+        };
+        let encoded = ToastArguments::serialize(&arguments).unwrap();
+        assert_eq!(
+            encoded.to_string(),
+            "action=markAsRead;conversationId=9813;text=a%3Db%3B 100%25;urgent=false"
+        );
+        let decoded = ToastArguments::decode(&encoded.to_string());
+        assert_eq!(decoded.deserialize::<Arguments>().unwrap(), arguments);
+
+        let arguments = Arguments {
+            action: Action::Reply,
+            reply_to: Some(7),
+            ..arguments
+        };
+        let encoded = ToastArguments::serialize(&arguments).unwrap();
+        assert_eq!(encoded.get("replyTo"), Some("7"));
+        assert_eq!(encoded.deserialize::<Arguments>().unwrap(), arguments);
+    }
+
+    #[test]
+    fn keys_without_values() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Flags {
+            read: bool,
+            muted: (),
+        }
+
+        let flags = ToastArguments::decode("read;muted");
+        assert_eq!(
+            flags.deserialize::<Flags>().unwrap(),
+            Flags {
+                read: true,
+                muted: ()
+            }
+        );
+        assert_eq!(
+            ToastArguments::serialize(&Flags {
+                read: true,
+                muted: ()
+            })
+            .unwrap()
+            .to_string(),
+            "read=true;muted"
+        );
+    }
+
+    #[test]
+    fn maps() {
+        let map = std::collections::BTreeMap::from([("b", 2), ("a", 1)]);
+        let arguments = ToastArguments::serialize(&map).unwrap();
+        assert_eq!(arguments.to_string(), "a=1;b=2");
+        assert_eq!(
+            arguments
+                .deserialize::<std::collections::BTreeMap<String, u8>>()
+                .unwrap(),
+            std::collections::BTreeMap::from([("a".to_owned(), 1), ("b".to_owned(), 2)])
+        );
+    }
+
+    #[test]
+    fn rejects_what_cannot_be_arguments() {
+        #[derive(Serialize)]
+        struct Nested {
+            inner: Vec<u8>,
+        }
+
+        match ToastArguments::serialize(&Nested { inner: vec![1] }) {
+            Err(Error::InvalidArguments(reason)) => {
+                assert_eq!(reason, "a sequence cannot be toast arguments")
+            }
+            result => panic!("expected invalid arguments, got {result:?}"),
+        }
+        assert!(matches!(
+            ToastArguments::serialize("text"),
+            Err(Error::InvalidArguments(_))
+        ));
+        assert!(matches!(
+            ToastArguments::decode("conversationId=abc").deserialize::<Arguments>(),
+            Err(Error::InvalidArguments(_))
+        ));
+        assert!(matches!(
+            ToastArguments::decode("action=reply").deserialize::<Arguments>(),
+            Err(Error::InvalidArguments(_))
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The platform side of showing toasts.
+//!
+//! [Notifier](crate::Notifier) talks to a [Backend] instead of WinRT directly,
+//! so everything above this module can be built and tested on any platform.
+
+use std::sync::Arc;
+
+use crate::{HistoryEntry, NotificationUpdateResult, Progress, Result, ToastEvent};
+
+#[cfg(test)]
+pub(crate) mod simulated;
+#[cfg(windows)]
+mod winrt;
+
+/// Called by the backend for every event Windows raises for a shown toast.
+pub(crate) type EventHandler = Arc<dyn Fn(ToastEvent) + Send + Sync>;
+
+/// Everything a backend needs to show a toast.
+// only the windows backend reads requests outside of tests
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) struct ShowRequest<'a> {
+    /// The toast xml payload.
+    pub xml: &'a str,
+    pub tag: &'a str,
+    /// The initial values of the progress bar, if any.
+    pub progress: Option<&'a Progress>,
+    pub handler: EventHandler,
+}
+
+pub(crate) trait Backend: Send + Sync {
+    fn show(&self, request: ShowRequest) -> Result<Box<dyn ShownToast>>;
+
+    fn update(&self, progress: &Progress) -> Result<NotificationUpdateResult>;
+
+    fn history(&self) -> Result<Vec<HistoryEntry>>;
+
+    /// Remove the toast with `tag`, in `group` if given.
+    fn remove(&self, tag: &str, group: Option<&str>) -> Result<()>;
+
+    fn remove_group(&self, group: &str) -> Result<()>;
+
+    fn clear(&self) -> Result<()>;
+}
+
+/// A toast that was handed to the platform by [Backend::show].
+pub(crate) trait ShownToast: Send {
+    fn hide(&self) -> Result<()>;
+}
+
+/// Create the backend of the current platform for `app_id`.
+#[cfg(windows)]
+pub(crate) fn platform(app_id: &str) -> Result<Arc<dyn Backend>> {
+    Ok(Arc::new(winrt::WinRtBackend::new(app_id)?))
+}
+
+/// Create the backend of the current platform for `app_id`.
+#[cfg(not(windows))]
+pub(crate) fn platform(_app_id: &str) -> Result<Arc<dyn Backend>> {
+    Err(crate::Error::BackendUnavailable { source: None })
+}
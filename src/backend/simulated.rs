@@ -0,0 +1,139 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! An in-memory [Backend] for tests.
+
+use std::sync::{Arc, Mutex};
+
+use super::{Backend, EventHandler, ShowRequest, ShownToast};
+use crate::{
+    HistoryEntry, NotificationUpdateResult, Progress, Result, ToastDismissalReason, ToastEvent,
+};
+
+/// A toast shown through the [SimulatedBackend].
+pub(crate) struct SimulatedToast {
+    pub xml: String,
+    pub tag: String,
+    handler: EventHandler,
+}
+
+#[derive(Default)]
+struct State {
+    /// Every toast ever shown, in order.
+    shown: Vec<Arc<SimulatedToast>>,
+    /// Tags of the toasts in the action center.
+    history: Vec<String>,
+    /// The event every shown toast immediately reacts with.
+    reaction: Option<ToastEvent>,
+}
+
+/// Pretends to show toasts and lets tests raise the events Windows would.
+#[derive(Default, Clone)]
+pub(crate) struct SimulatedBackend {
+    state: Arc<Mutex<State>>,
+}
+
+impl SimulatedBackend {
+    /// Make every toast shown from now on raise `event` right away.
+    pub fn react_with(&self, event: ToastEvent) {
+        self.state.lock().unwrap().reaction = Some(event);
+    }
+
+    /// Raise `event` for the toast shown with `tag`.
+    pub fn raise(&self, tag: &str, event: ToastEvent) {
+        let toast = self
+            .shown()
+            .into_iter()
+            .rev()
+            .find(|toast| toast.tag == tag)
+            .expect("no toast with this tag was shown");
+        (toast.handler)(event);
+    }
+
+    /// Every toast shown so far.
+    pub fn shown(&self) -> Vec<Arc<SimulatedToast>> {
+        self.state.lock().unwrap().shown.clone()
+    }
+}
+
+impl Backend for SimulatedBackend {
+    fn show(&self, request: ShowRequest) -> Result<Box<dyn ShownToast>> {
+        let toast = Arc::new(SimulatedToast {
+            xml: request.xml.to_owned(),
+            tag: request.tag.to_owned(),
+            handler: request.handler,
+        });
+
+        let reaction = {
+            let mut state = self.state.lock().unwrap();
+            state.shown.push(toast.clone());
+            state.history.retain(|tag| tag != &toast.tag);
+            state.history.push(toast.tag.clone());
+            state.reaction.clone()
+        };
+
+        if let Some(event) = reaction {
+            (toast.handler)(event);
+        }
+
+        Ok(Box::new(SimulatedShownToast {
+            backend: self.clone(),
+            toast,
+        }))
+    }
+
+    fn update(&self, progress: &Progress) -> Result<NotificationUpdateResult> {
+        let state = self.state.lock().unwrap();
+        Ok(if state.history.contains(&progress.tag) {
+            NotificationUpdateResult::Succeeded
+        } else {
+            NotificationUpdateResult::NotificationNotFound
+        })
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .history
+            .iter()
+            .map(|tag| HistoryEntry {
+                tag: tag.clone(),
+                group: String::new(),
+            })
+            .collect())
+    }
+
+    fn remove(&self, tag: &str, group: Option<&str>) -> Result<()> {
+        // toasts are shown without a group
+        if group.map_or(true, str::is_empty) {
+            self.state.lock().unwrap().history.retain(|t| t != tag);
+        }
+        Ok(())
+    }
+
+    fn remove_group(&self, _group: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.state.lock().unwrap().history.clear();
+        Ok(())
+    }
+}
+
+struct SimulatedShownToast {
+    backend: SimulatedBackend,
+    toast: Arc<SimulatedToast>,
+}
+
+impl ShownToast for SimulatedShownToast {
+    fn hide(&self) -> Result<()> {
+        self.backend.remove(&self.toast.tag, None)?;
+        (self.toast.handler)(ToastEvent::Dismissed(Some(
+            ToastDismissalReason::ApplicationHidden,
+        )));
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use windows::{
+    core::{IInspectable, Interface, HSTRING},
+    Data::Xml::Dom::XmlDocument,
+    Foundation::{Collections::StringMap, IReference, TypedEventHandler},
+    UI::Notifications::{
+        NotificationData, ToastActivatedEventArgs, ToastDismissedEventArgs, ToastFailedEventArgs,
+        ToastNotification, ToastNotificationHistory, ToastNotificationManager, ToastNotifier,
+    },
+};
+
+use super::{Backend, ShowRequest, ShownToast};
+use crate::{
+    Activation, Error, HistoryEntry, NotificationUpdateResult, Progress, Result,
+    ToastDismissalReason, ToastEvent, ToastFailure,
+};
+
+pub(crate) struct WinRtBackend {
+    app_id: HSTRING,
+    notifier: ToastNotifier,
+}
+
+impl WinRtBackend {
+    pub(crate) fn new(app_id: &str) -> Result<WinRtBackend> {
+        let app_id = HSTRING::from(app_id);
+        Ok(WinRtBackend {
+            notifier: ToastNotificationManager::CreateToastNotifierWithId(&app_id)
+                .map_err(Error::classified)?,
+            app_id,
+        })
+    }
+}
+
+impl Backend for WinRtBackend {
+    fn show(&self, request: ShowRequest) -> Result<Box<dyn ShownToast>> {
+        //using this to get an instance of XmlDocument
+        let toast_xml = XmlDocument::new()?;
+        toast_xml
+            .LoadXml(&HSTRING::from(request.xml))
+            .map_err(Error::classified)?;
+
+        // Create the toast and attach event listeners
+        let notification = ToastNotification::CreateToastNotification(&toast_xml)?;
+
+        let handler = request.handler.clone();
+        notification.Activated(&TypedEventHandler::new(move |_, insp| {
+            handler(ToastEvent::Activated(get_activation(insp)));
+            Ok(())
+        }))?;
+
+        let handler = request.handler.clone();
+        notification.Dismissed(&TypedEventHandler::new(
+            move |_, args: &Option<ToastDismissedEventArgs>| {
+                handler(ToastEvent::Dismissed(get_dismissed_reason(args)));
+                Ok(())
+            },
+        ))?;
+
+        let handler = request.handler;
+        notification.Failed(&TypedEventHandler::new(
+            move |_, args: &Option<ToastFailedEventArgs>| {
+                let code = args
+                    .as_ref()
+                    .and_then(|args| args.ErrorCode().ok())
+                    .map_or(0, |code| code.0);
+                handler(ToastEvent::Failed(ToastFailure::new(code)));
+                Ok(())
+            },
+        ))?;
+
+        notification.SetTag(&HSTRING::from(request.tag))?;
+
+        if let Some(progress) = request.progress {
+            notification.SetData(&progress_data(progress, 1)?)?;
+        }
+
+        self.notifier
+            .Show(&notification)
+            .map_err(|error| Error::refused(error, request.xml, request.tag))?;
+
+        Ok(Box::new(WinRtToast {
+            notifier: self.notifier.clone(),
+            notification,
+        }))
+    }
+
+    fn update(&self, progress: &Progress) -> Result<NotificationUpdateResult> {
+        let data = progress_data(progress, 2)?;
+
+        let result = self
+            .notifier
+            .UpdateWithTag(&data, &HSTRING::from(&progress.tag))?;
+
+        Ok(match result {
+            windows::UI::Notifications::NotificationUpdateResult::Succeeded => {
+                NotificationUpdateResult::Succeeded
+            }
+            windows::UI::Notifications::NotificationUpdateResult::NotificationNotFound => {
+                NotificationUpdateResult::NotificationNotFound
+            }
+            _ => NotificationUpdateResult::Failed,
+        })
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        let mut entries = Vec::new();
+        for notification in history()?.GetHistoryWithId(&self.app_id)? {
+            entries.push(HistoryEntry {
+                tag: notification.Tag()?.to_string_lossy(),
+                group: notification.Group()?.to_string_lossy(),
+            });
+        }
+        Ok(entries)
+    }
+
+    fn remove(&self, tag: &str, group: Option<&str>) -> Result<()> {
+        let history = history()?;
+        let tag = HSTRING::from(tag);
+        match group {
+            Some(group) => {
+                history.RemoveGroupedTagWithId(&tag, &HSTRING::from(group), &self.app_id)
+            }
+            None => history.Remove(&tag),
+        }
+        .map_err(Into::into)
+    }
+
+    fn remove_group(&self, group: &str) -> Result<()> {
+        history()?
+            .RemoveGroupWithId(&HSTRING::from(group), &self.app_id)
+            .map_err(Into::into)
+    }
+
+    fn clear(&self) -> Result<()> {
+        history()?.ClearWithId(&self.app_id).map_err(Into::into)
+    }
+}
+
+struct WinRtToast {
+    notifier: ToastNotifier,
+    notification: ToastNotification,
+}
+
+impl ShownToast for WinRtToast {
+    fn hide(&self) -> Result<()> {
+        self.notifier.Hide(&self.notification).map_err(Into::into)
+    }
+}
+
+// the history is not thread safe, so it is fetched whenever it is needed
+fn history() -> Result<ToastNotificationHistory> {
+    ToastNotificationManager::History().map_err(Into::into)
+}
+
+fn progress_data(progress: &Progress, sequence_number: u32) -> Result<NotificationData> {
+    let map = StringMap::new()?;
+    map.Insert(
+        &HSTRING::from("progressTitle"),
+        &HSTRING::from(&progress.title),
+    )?;
+    map.Insert(
+        &HSTRING::from("progressStatus"),
+        &HSTRING::from(&progress.status),
+    )?;
+    map.Insert(
+        &HSTRING::from("progressValue"),
+        &HSTRING::from(progress.value.to_string()),
+    )?;
+    map.Insert(
+        &HSTRING::from("progressValueString"),
+        &HSTRING::from(&progress.value_string),
+    )?;
+
+    NotificationData::CreateNotificationDataWithValuesAndSequenceNumber(&map, sequence_number)
+        .map_err(Into::into)
+}
+
+fn get_activation(insp: &Option<IInspectable>) -> Activation {
+    let mut activation = Activation::default();
+    if let Some(args) = insp
+        .as_ref()
+        .and_then(|insp| insp.cast::<ToastActivatedEventArgs>().ok())
+    {
+        if let Ok(arguments) = args.Arguments() {
+            if !arguments.is_empty() {
+                activation.arguments = Some(arguments.to_string_lossy());
+            }
+        }
+
+        // only available since the Fall Creators Update
+        if let Ok(user_input) = args.UserInput() {
+            for pair in user_input {
+                if let (Ok(key), Some(value)) = (pair.Key(), get_string(pair.Value().ok())) {
+                    activation.user_input.insert(key.to_string_lossy(), value);
+                }
+            }
+        }
+    }
+    activation
+}
+
+/// Unbox a string value from the user input of an activation.
+fn get_string(value: Option<IInspectable>) -> Option<String> {
+    let value = value?.cast::<IReference<HSTRING>>().ok()?;
+    value.Value().ok().map(|value| value.to_string_lossy())
+}
+
+fn get_dismissed_reason(args: &Option<ToastDismissedEventArgs>) -> Option<ToastDismissalReason> {
+    let reason = args.as_ref()?.Reason().ok()?;
+    match reason {
+        windows::UI::Notifications::ToastDismissalReason::UserCanceled => {
+            Some(ToastDismissalReason::UserCanceled)
+        }
+        windows::UI::Notifications::ToastDismissalReason::ApplicationHidden => {
+            Some(ToastDismissalReason::ApplicationHidden)
+        }
+        windows::UI::Notifications::ToastDismissalReason::TimedOut => {
+            Some(ToastDismissalReason::TimedOut)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,384 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration as StdDuration, SystemTime};
+
+use sha2::{Digest, Sha256};
+
+use crate::Result;
+
+/// Windows only loads toast images from files, so in-memory images are written to this directory.
+///
+/// Files are named after the hash of their content, so an image that is shown again reuses its file.
+/// Whenever a new file is written, files not used for longer than [max_age](Self::max_age) are removed,
+/// and then the least recently used files until the directory is no larger than [max_size](Self::max_size).
+/// A [Notifier](crate::Notifier) cleans the cache once all images of a toast are stored,
+/// so the images of the toast it is about to show are never removed.
+///
+/// # Example
+/// ```rust,no_run
+/// use tauri_winrt_notification::{ImageCache, ImageSource, Notifier, Toast};
+///
+/// let cache = ImageCache::new(std::env::temp_dir().join("my-app-toasts")).max_size(10 * 1024 * 1024);
+/// let notifier = Notifier::new(Toast::POWERSHELL_APP_ID)
+///     .expect("notifier unavailable")
+///     .with_image_cache(cache);
+///
+/// let avatar: &[u8] = include_bytes!("../resources/test/chick.jpeg");
+/// let toast = Toast::new(Toast::POWERSHELL_APP_ID).image(ImageSource::bytes(avatar), "avatar");
+/// notifier.show(&toast).expect("notification failed");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageCache {
+    dir: PathBuf,
+    max_size: u64,
+    max_age: StdDuration,
+    /// Whether [store](Self::store) cleans the cache, `false` while the images of a toast are prepared.
+    clean_on_store: bool,
+}
+
+impl ImageCache {
+    /// A cache in `dir`, which is created when the first image is stored.
+    ///
+    /// The cache holds at most 50 MB of images for up to 7 days by default.
+    pub fn new(dir: impl Into<PathBuf>) -> ImageCache {
+        ImageCache {
+            dir: dir.into(),
+            max_size: 50 * 1024 * 1024,
+            max_age: StdDuration::from_secs(7 * 24 * 60 * 60),
+            clean_on_store: true,
+        }
+    }
+
+    /// Set the largest total size in bytes of the cached images.
+    pub fn max_size(mut self, bytes: u64) -> ImageCache {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Set how long cached images are kept.
+    pub fn max_age(mut self, age: StdDuration) -> ImageCache {
+        self.max_age = age;
+        self
+    }
+
+    /// The directory the images are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path `data` is stored at, whether it was stored yet or not.
+    pub fn path_of(&self, data: &[u8]) -> PathBuf {
+        let mut name = String::with_capacity(64 + 5);
+        for byte in Sha256::digest(data) {
+            let _ = write!(name, "{byte:02x}");
+        }
+        name.push('.');
+        name.push_str(extension(data));
+        self.dir.join(name)
+    }
+
+    /// Write `data` to the cache unless it is already there, and return the path of its file.
+    pub fn store(&self, data: &[u8]) -> Result<PathBuf> {
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+
+        let path = self.path_of(data);
+        let stored = fs::metadata(&path).is_ok_and(|metadata| metadata.len() == data.len() as u64);
+        if stored {
+            // mark it as used, the file may be removed or still be shown meanwhile
+            let _ = touch(&path, data);
+        } else {
+            fs::create_dir_all(&self.dir)?;
+            // write to a temporary file of this write only, so a toast never sees a partially written image
+            let temporary = path.with_extension(format!(
+                "{}-{}.{TEMPORARY_EXTENSION}",
+                std::process::id(),
+                WRITES.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::write(&temporary, data)?;
+            fs::rename(&temporary, &path)?;
+            if self.clean_on_store {
+                // best effort, the image is stored either way
+                let _ = self.clean_at(SystemTime::now(), &[&path]);
+            }
+        }
+        Ok(path)
+    }
+
+    /// Remove the files that are too old or over the size budget.
+    ///
+    /// Files that cannot be removed, like images of a toast still on screen, are skipped.
+    /// Fails only if the directory cannot be read.
+    pub fn clean(&self) -> Result<()> {
+        self.clean_at(SystemTime::now(), &[])
+    }
+
+    /// The same cache, but [store](Self::store) leaves cleaning to [clean_keeping](Self::clean_keeping).
+    pub(crate) fn without_cleaning(&self) -> ImageCache {
+        ImageCache {
+            clean_on_store: false,
+            ..self.clone()
+        }
+    }
+
+    /// [Clean](Self::clean) the cache, but never remove the files in `keep`.
+    ///
+    /// Best effort, errors are ignored.
+    pub(crate) fn clean_keeping(&self, keep: &[&Path]) {
+        let _ = self.clean_at(SystemTime::now(), keep);
+    }
+
+    fn clean_at(&self, now: SystemTime, keep: &[&Path]) -> Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut files = Vec::new();
+        let mut kept = 0;
+        for entry in entries {
+            // skip files removed meanwhile
+            let Ok(entry) = entry else { continue };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            // being written by another store, unless left behind by a crash
+            if entry.path().extension() == Some(TEMPORARY_EXTENSION.as_ref()) {
+                let abandoned = now
+                    .duration_since(metadata.modified()?)
+                    .is_ok_and(|age| age > self.max_age);
+                if abandoned {
+                    let _ = fs::remove_file(entry.path());
+                }
+                continue;
+            }
+            if keep.contains(&entry.path().as_path()) {
+                kept += metadata.len();
+            } else {
+                files.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+        let mut size: u64 = kept + files.iter().map(|(_, len, _)| len).sum::<u64>();
+
+        // least recently used first
+        files.sort();
+        for (modified, len, path) in files {
+            let expired = now
+                .duration_since(modified)
+                .is_ok_and(|age| age > self.max_age);
+            if expired || size > self.max_size {
+                // another process may have removed it already, or a toast on screen may still hold it
+                match fs::remove_file(&path) {
+                    Ok(()) => size -= len,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => size -= len,
+                    Err(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for ImageCache {
+    /// A cache in the temporary directory of the system.
+    fn default() -> Self {
+        ImageCache::new(
+            std::env::temp_dir()
+                .join("tauri-winrt-notification")
+                .join("images"),
+        )
+    }
+}
+
+/// Update the modification time of the file at `path` holding `data`,
+/// by writing its first byte again, as `File::set_modified` needs Rust 1.75.
+fn touch(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    file.write_all(&data[..data.len().min(1)])
+}
+
+/// The extension of files that are still being written.
+const TEMPORARY_EXTENSION: &str = "tmp";
+
+/// A file extension for the image format of `data`.
+fn extension(data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG") {
+        "png"
+    } else if data.starts_with(b"\xff\xd8") {
+        "jpg"
+    } else if data.starts_with(b"GIF8") {
+        "gif"
+    } else if data.starts_with(b"BM") {
+        "bmp"
+    } else if data.starts_with(b"\0\0\x01\0") {
+        "ico"
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        "webp"
+    } else {
+        "img"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh cache in a directory of its own.
+    fn cache(name: &str) -> ImageCache {
+        let dir = std::env::temp_dir()
+            .join("tauri-winrt-notification-tests")
+            .join(format!("{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ImageCache::new(dir)
+    }
+
+    fn files(cache: &ImageCache) -> usize {
+        fs::read_dir(cache.dir()).unwrap().count()
+    }
+
+    #[test]
+    fn stores_by_content() {
+        let cache = cache("stores_by_content");
+        let png = b"\x89PNG\r\n\x1a\nimage";
+
+        let path = cache.store(png).unwrap();
+        assert_eq!(path, cache.path_of(png));
+        assert_eq!(path.extension().unwrap(), "png");
+        assert_eq!(fs::read(&path).unwrap(), png);
+
+        // reused
+        assert_eq!(cache.store(png).unwrap(), path);
+        assert_eq!(files(&cache), 1);
+
+        let other = cache.store(b"\xff\xd8other").unwrap();
+        assert_ne!(other, path);
+        assert_eq!(other.extension().unwrap(), "jpg");
+        assert_eq!(files(&cache), 2);
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn keeps_to_size_budget() {
+        let cache = cache("keeps_to_size_budget").max_size(25);
+        let first = cache.store(&[1; 10]).unwrap();
+        let second = cache.store(&[2; 10]).unwrap();
+        assert!(first.exists() && second.exists());
+
+        // the newest file is always kept, the oldest ones go
+        let third = cache.store(&[3; 10]).unwrap();
+        assert!(third.exists());
+        assert_eq!(files(&cache), 2);
+
+        let huge = cache.store(&[4; 100]).unwrap();
+        assert!(huge.exists());
+        assert_eq!(files(&cache), 1);
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn removes_old_files() {
+        let cache = cache("removes_old_files").max_age(StdDuration::from_secs(60));
+        let path = cache.store(b"image").unwrap();
+
+        cache.clean().unwrap();
+        assert!(path.exists());
+
+        let later = SystemTime::now() + StdDuration::from_secs(120);
+        cache.clean_at(later, &[]).unwrap();
+        assert!(!path.exists());
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_images_of_a_toast() {
+        let cache = cache("keeps_the_images_of_a_toast").max_age(StdDuration::from_secs(60));
+        let reused = cache.store(b"reused").unwrap();
+        let old = cache.store(b"old").unwrap();
+
+        // storing without cleaning, as for the images of a toast
+        let preparing = cache.without_cleaning().max_size(0);
+        assert_eq!(preparing.store(b"reused").unwrap(), reused);
+        let new = preparing.store(b"new").unwrap();
+        assert_eq!(files(&cache), 3);
+
+        let later = SystemTime::now() + StdDuration::from_secs(120);
+        cache.clean_at(later, &[&reused, &new]).unwrap();
+        assert!(reused.exists() && new.exists());
+        assert!(!old.exists());
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn stores_from_many_threads() {
+        let cache = cache("stores_from_many_threads");
+        let paths: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                std::thread::spawn(move || cache.store(&[7; 100_000]).unwrap())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+        assert!(paths.iter().all(|path| *path == paths[0]));
+        assert_eq!(files(&cache), 1);
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn skips_files_being_written() {
+        let cache = cache("skips_files_being_written").max_size(0);
+        fs::create_dir_all(cache.dir()).unwrap();
+        let writing = cache.dir().join("image.1-0.tmp");
+        fs::write(&writing, [1; 10]).unwrap();
+
+        cache.clean().unwrap();
+        assert!(writing.exists());
+
+        let later = SystemTime::now() + StdDuration::from_secs(8 * 24 * 60 * 60);
+        cache.clean_at(later, &[]).unwrap();
+        assert!(!writing.exists());
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = cache("evicts_least_recently_used").max_size(25);
+        let tick = || std::thread::sleep(StdDuration::from_millis(20));
+        let first = cache.store(&[1; 10]).unwrap();
+        tick();
+        let second = cache.store(&[2; 10]).unwrap();
+        tick();
+
+        // reusing the older file makes the other one the least recently used
+        assert_eq!(cache.store(&[1; 10]).unwrap(), first);
+        tick();
+        let third = cache.store(&[3; 10]).unwrap();
+        assert!(first.exists() && third.exists());
+        assert!(!second.exists());
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn clean_without_directory() {
+        cache("clean_without_directory").clean().unwrap();
+    }
+}
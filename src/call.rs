@@ -0,0 +1,176 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{ImageOptions, ImageSource, Scenario, Toast};
+
+/// The toast of an incoming call, with the caller centered and buttons to answer it.
+///
+/// Builds a [Scenario::IncomingCall] toast with the caller's name and details centered,
+/// an optional circular caller image, and buttons to accept, reply with a message, or decline.
+/// The ringtone loops until the user picks one.
+///
+/// # Example
+/// ```rust,no_run
+/// use tauri_winrt_notification::{ImageSource, IncomingCall, Notifier, Toast};
+///
+/// let notifier = Notifier::new(Toast::POWERSHELL_APP_ID).expect("notifier unavailable");
+/// let toast = IncomingCall::new("Andrew Bares")
+///     .detail("Mobile")
+///     .caller_image(ImageSource::uri("ms-appx:///andrew.png").unwrap(), "Andrew")
+///     .message("Text reply", "message")
+///     .toast();
+/// notifier.show(&toast).expect("notification failed");
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncomingCall {
+    caller: String,
+    detail: Option<String>,
+    image: Option<(ImageSource, String)>,
+    /// `(content, action)` of every button.
+    accept: (String, String),
+    message: Option<(String, String)>,
+    decline: (String, String),
+}
+
+impl IncomingCall {
+    /// A call from `caller`, with an `Accept` and a `Decline` button sending `accept` and `decline`.
+    pub fn new(caller: &str) -> IncomingCall {
+        IncomingCall {
+            caller: caller.to_owned(),
+            detail: None,
+            image: None,
+            accept: ("Accept".to_owned(), "accept".to_owned()),
+            message: None,
+            decline: ("Decline".to_owned(), "decline".to_owned()),
+        }
+    }
+
+    /// Set the text below the caller, like the number or the kind of call.
+    pub fn detail(mut self, detail: &str) -> IncomingCall {
+        self.detail = Some(detail.to_owned());
+        self
+    }
+
+    /// Show a picture of the caller, cropped to a circle.
+    pub fn caller_image(mut self, source: impl Into<ImageSource>, alt_text: &str) -> IncomingCall {
+        self.image = Some((source.into(), alt_text.to_owned()));
+        self
+    }
+
+    /// Set the button accepting the call.
+    pub fn accept(mut self, content: &str, action: &str) -> IncomingCall {
+        self.accept = (content.to_owned(), action.to_owned());
+        self
+    }
+
+    /// Add a button replying with a message instead of answering, between accept and decline.
+    pub fn message(mut self, content: &str, action: &str) -> IncomingCall {
+        self.message = Some((content.to_owned(), action.to_owned()));
+        self
+    }
+
+    /// Set the button declining the call.
+    pub fn decline(mut self, content: &str, action: &str) -> IncomingCall {
+        self.decline = (content.to_owned(), action.to_owned());
+        self
+    }
+
+    /// The toast of the call without an app id, which can be changed further before it is shown
+    /// through a [Notifier](crate::Notifier).
+    pub fn toast(&self) -> Toast {
+        let mut toast = Toast::default()
+            .scenario(Scenario::IncomingCall)
+            .center_call_texts(true)
+            .title(&self.caller);
+        if let Some(detail) = &self.detail {
+            toast = toast.text1(detail);
+        }
+        if let Some((source, alt_text)) = &self.image {
+            let options = ImageOptions {
+                circle_crop: true,
+                ..ImageOptions::default()
+            };
+            toast = toast.image_with(source.clone(), alt_text, options);
+        }
+        [
+            Some(&self.accept),
+            self.message.as_ref(),
+            Some(&self.decline),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(toast, |toast, (content, action)| {
+            toast.add_button(content, action)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Downgrade, Rendered, TargetProfile};
+
+    fn call() -> IncomingCall {
+        IncomingCall::new("Andrew Bares")
+            .detail("Mobile")
+            .caller_image(ImageSource::uri("ms-appx:///andrew.png").unwrap(), "Andrew")
+            .message("Text reply", "message")
+    }
+
+    #[test]
+    fn windows_11() {
+        let toast = call().toast();
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_11);
+        assert_eq!(downgrades, []);
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<toast scenario="incomingCall"><visual><binding template="ToastGeneric">"#,
+                r#"<image id="1" hint-crop="circle" src="ms-appx:///andrew.png" alt="Andrew"/>"#,
+                r#"<text id="1" hint-callScenarioCenterAlign="true">Andrew Bares</text>"#,
+                r#"<text id="2" hint-callScenarioCenterAlign="true">Mobile</text>"#,
+                r#"</binding></visual>"#,
+                r#"<audio loop="true" src="ms-winsoundevent:Notification.Looping.Call"/>"#,
+                r#"<actions><action content='Accept' arguments='accept'/>"#,
+                r#"<action content='Text reply' arguments='message'/>"#,
+                r#"<action content='Decline' arguments='decline'/></actions>"#,
+                r#"</toast>"#
+            )
+        );
+        assert_eq!(toast.validate_for(TargetProfile::WINDOWS_11), []);
+    }
+
+    #[test]
+    fn before_windows_11() {
+        let toast = call().toast();
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::FALL_CREATORS_UPDATE);
+        assert!(xml.contains(r#"<text id="1">Andrew Bares</text>"#));
+        assert!(xml.contains(r#"hint-crop="circle""#));
+        assert_eq!(downgrades, [Downgrade::CallTextsNotCentered]);
+
+        let Rendered { downgrades, .. } = toast.render(TargetProfile::WINDOWS_10);
+        assert_eq!(
+            downgrades,
+            [
+                Downgrade::CircleCropDropped,
+                Downgrade::CallTextsNotCentered
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_buttons() {
+        let xml = IncomingCall::new("Andrew Bares")
+            .accept("Video", "video")
+            .decline("Ignore", "ignore")
+            .toast()
+            .to_xml(TargetProfile::WINDOWS_11);
+        assert!(xml.contains(concat!(
+            r#"<actions><action content='Video' arguments='video'/>"#,
+            r#"<action content='Ignore' arguments='ignore'/></actions>"#
+        )));
+        assert!(!xml.contains("<image"));
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Reads the size of an image from its header, for the formats toasts support.
+
+use std::path::Path;
+
+/// The `(width, height)` of the image at `path`, `None` if it cannot be read or the format is unknown.
+pub(crate) fn read(path: &Path) -> Option<(u32, u32)> {
+    parse(&std::fs::read(path).ok()?)
+}
+
+pub(crate) fn parse(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // the IHDR chunk always comes first
+        Some((be32(bytes, 16)?, be32(bytes, 20)?))
+    } else if bytes.starts_with(b"GIF8") {
+        Some((le16(bytes, 6)? as u32, le16(bytes, 8)? as u32))
+    } else if bytes.starts_with(b"BM") {
+        let height = le32(bytes, 22)? as i32;
+        Some((le32(bytes, 18)?, height.unsigned_abs()))
+    } else if bytes.starts_with(b"\xff\xd8") {
+        jpeg(bytes)
+    } else {
+        None
+    }
+}
+
+fn jpeg(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut offset = 2;
+    loop {
+        if *bytes.get(offset)? != 0xff {
+            return None;
+        }
+        let marker = *bytes.get(offset + 1)?;
+        match marker {
+            // padding
+            0xff => offset += 1,
+            // markers without a length
+            0x01 | 0xd0..=0xd7 => offset += 2,
+            // start of frame, except for DHT, JPG and DAC
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let height = be16(bytes, offset + 5)?;
+                let width = be16(bytes, offset + 7)?;
+                return Some((width as u32, height as u32));
+            }
+            _ => offset += 2 + be16(bytes, offset + 2)? as usize,
+        }
+    }
+}
+
+fn be16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn be32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn le16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn le32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jpeg() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/test/chick.jpeg");
+        assert_eq!(read(&path), Some((288, 192)));
+    }
+
+    #[test]
+    fn png() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(64u32.to_be_bytes());
+        png.extend(32u32.to_be_bytes());
+        assert_eq!(parse(&png), Some((64, 32)));
+    }
+
+    #[test]
+    fn gif_and_bmp() {
+        assert_eq!(parse(b"GIF89a\x10\x00\x20\x00"), Some((16, 32)));
+
+        let mut bmp = vec![0; 26];
+        bmp[..2].copy_from_slice(b"BM");
+        bmp[18..22].copy_from_slice(&48u32.to_le_bytes());
+        // top-down bitmaps have a negative height
+        bmp[22..26].copy_from_slice(&(-48i32).to_le_bytes());
+        assert_eq!(parse(&bmp), Some((48, 48)));
+    }
+
+    #[test]
+    fn unknown_or_truncated() {
+        assert_eq!(parse(b"RIFF"), None);
+        assert_eq!(parse(b"\x89PNG\r\n\x1a\n\0\0"), None);
+        assert_eq!(parse(b"\xff\xd8\xff\xe0\x00\x10"), None);
+        assert_eq!(read(Path::new("does/not/exist.png")), None);
+    }
+}
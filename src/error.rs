@@ -0,0 +1,383 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::Violation;
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Any other error reported by Windows.
+    #[cfg(windows)]
+    #[error("Windows API error: {0}")]
+    Os(windows::core::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The notification platform is not available, always the case on platforms other than Windows.
+    ///
+    /// `source` is the error Windows reported, `None` on other platforms.
+    #[error("toast notifications are not available on this platform")]
+    BackendUnavailable { source: Option<OsError> },
+    /// The toast xml is malformed.
+    ///
+    /// `line` and `column` start at 1 and are 0 if the position is unknown,
+    /// `snippet` is the offending line of the xml.
+    #[error("invalid toast xml{}: {message}{}", position(*line, *column), near(snippet))]
+    InvalidXml {
+        message: String,
+        line: usize,
+        column: usize,
+        snippet: String,
+    },
+    /// The AppUserModelID is not registered with Windows.
+    #[error("the app id is not registered with Windows")]
+    UnknownAppId { source: OsError },
+    /// The toast was created without an app id, so it can only be shown through a [Notifier](crate::Notifier).
+    #[error("the toast has no app id, show it through a notifier")]
+    MissingAppId,
+    /// Notifications are turned off for the app, for all apps or by group policy.
+    #[error("notifications are turned off for this app or by group policy")]
+    NotificationsDisabled { source: OsError },
+    /// The running version of Windows does not support `feature`.
+    #[error("not supported by this version of Windows: {feature}")]
+    Unsupported { feature: String, source: OsError },
+    /// A URI is malformed or uses a scheme that is not allowed where it is used.
+    #[error("invalid URI `{uri}`: {reason}")]
+    InvalidUri { uri: String, reason: String },
+    /// An image could not be decoded or encoded.
+    #[error("invalid image: {0}")]
+    InvalidImage(String),
+    /// A value cannot be turned into [ToastArguments](crate::ToastArguments), or the arguments into a value.
+    #[error("invalid toast arguments: {0}")]
+    InvalidArguments(String),
+    /// A [remote image](crate::RemoteImages) could not be downloaded.
+    #[error("failed to download `{url}`: {reason}")]
+    Download { url: String, reason: String },
+    /// The toast breaks rules Windows imposes on toasts.
+    ///
+    /// Found by a [strict](crate::Notifier::strict) notifier or [LoopPolicy::Reject](crate::LoopPolicy::Reject)
+    /// before the toast is shown, or reported by Windows when it refuses to show the toast.
+    #[error("invalid toast: {}", list(.0))]
+    Violations(Vec<Violation>),
+    /// Windows failed to show the toast for a reason without a more specific variant.
+    #[error("Windows failed to show the toast (HRESULT {0:#010X})")]
+    ToastFailed(i32),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error reported by Windows, the source of the [Error](enum@Error) variants Windows errors are classified into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsError {
+    code: i32,
+    message: String,
+}
+
+impl OsError {
+    fn new(code: i32, message: String) -> OsError {
+        OsError { code, message }
+    }
+
+    /// The HRESULT error code.
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// The message Windows gave for the error, empty if there is none.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for OsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "HRESULT {:#010X}", self.code)
+        } else {
+            write!(f, "{} (HRESULT {:#010X})", self.message, self.code)
+        }
+    }
+}
+
+impl std::error::Error for OsError {}
+
+#[cfg(windows)]
+impl From<&windows::core::Error> for OsError {
+    fn from(error: &windows::core::Error) -> Self {
+        OsError::new(error.code().0, error.message())
+    }
+}
+
+fn position(line: usize, column: usize) -> String {
+    if line == 0 {
+        String::new()
+    } else {
+        format!(" at line {line}, column {column}")
+    }
+}
+
+fn list(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn near(snippet: &str) -> String {
+    if snippet.is_empty() {
+        String::new()
+    } else {
+        format!(" near `{snippet}`")
+    }
+}
+
+// error codes from winerror.h
+const E_NOTIMPL: u32 = 0x80004001;
+const E_NOINTERFACE: u32 = 0x80004002;
+const REGDB_E_CLASSNOTREG: u32 = 0x80040154;
+const ERROR_NOT_SUPPORTED: u32 = 0x80070032;
+const ERROR_NOT_FOUND: u32 = 0x80070490;
+const WPN_E_INVALID_APP: u32 = 0x803E0102;
+const WPN_E_PLATFORM_UNAVAILABLE: u32 = 0x803E0105;
+const WPN_E_NOTIFICATION_DISABLED: u32 = 0x803E0111;
+const WPN_E_NOTIFICATION_TYPE_DISABLED: u32 = 0x803E0114;
+const WPN_E_NOTIFICATION_SIZE: u32 = 0x803E0115;
+const WPN_E_TAG_SIZE: u32 = 0x803E0116;
+const WPN_E_TAG_ALPHANUMERIC: u32 = 0x803E012A;
+/// The MSXML parser errors returned by `XmlDocument::LoadXml`.
+const XML_E_RANGE: std::ops::RangeInclusive<u32> = 0xC00CE000..=0xC00CEFFF;
+
+impl Error {
+    /// Map an HRESULT error code Windows reported for a toast it failed to show to an error.
+    ///
+    /// Codes without a more specific variant become [Error::ToastFailed].
+    pub fn from_hresult(code: i32) -> Error {
+        Error::classify(&OsError::new(code, String::new())).unwrap_or(Error::ToastFailed(code))
+    }
+
+    /// The specific variant for an error of creating a notifier, loading a payload or showing a toast.
+    ///
+    /// Only meaningful for these calls, Windows reuses codes like `ERROR_NOT_FOUND` for unrelated errors elsewhere.
+    fn classify(source: &OsError) -> Option<Error> {
+        let source = source.clone();
+        Some(match source.code as u32 {
+            ERROR_NOT_FOUND | WPN_E_INVALID_APP => Error::UnknownAppId { source },
+            WPN_E_NOTIFICATION_DISABLED | WPN_E_NOTIFICATION_TYPE_DISABLED => {
+                Error::NotificationsDisabled { source }
+            }
+            WPN_E_PLATFORM_UNAVAILABLE => Error::BackendUnavailable {
+                source: Some(source),
+            },
+            REGDB_E_CLASSNOTREG => Error::Unsupported {
+                feature: "toast notifications".to_owned(),
+                source,
+            },
+            E_NOTIMPL | E_NOINTERFACE | ERROR_NOT_SUPPORTED => Error::Unsupported {
+                feature: "this operation".to_owned(),
+                source,
+            },
+            code if XML_E_RANGE.contains(&code) => Error::InvalidXml {
+                message: format!("the xml parser failed with {code:#010X}"),
+                line: 0,
+                column: 0,
+                snippet: String::new(),
+            },
+            _ => return None,
+        })
+    }
+
+    /// Like [classify](Self::classify), but the toast with the payload `xml` and `tag` may also be refused.
+    // only the windows backend shows toasts outside of tests
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn classify_show(source: &OsError, xml: &str, tag: &str) -> Option<Error> {
+        let invalid_tag = |reason: &str| Violation::InvalidTag {
+            tag: tag.to_owned(),
+            reason: reason.to_owned(),
+        };
+        let violation = match source.code as u32 {
+            WPN_E_NOTIFICATION_SIZE => Violation::PayloadTooLarge { size: xml.len() },
+            WPN_E_TAG_SIZE => invalid_tag("too long"),
+            WPN_E_TAG_ALPHANUMERIC => invalid_tag("must be alphanumeric"),
+            _ => return Error::classify(source),
+        };
+        Some(Error::Violations(vec![violation]))
+    }
+}
+
+#[cfg(windows)]
+impl Error {
+    /// An error of creating a notifier or loading a payload, see [classify](Self::classify).
+    pub(crate) fn classified(error: windows::core::Error) -> Error {
+        Error::classify(&OsError::from(&error)).unwrap_or(Error::Os(error))
+    }
+
+    /// An error of showing the toast with the payload `xml` and `tag`, see [classify_show](Self::classify_show).
+    pub(crate) fn refused(error: windows::core::Error, xml: &str, tag: &str) -> Error {
+        Error::classify_show(&OsError::from(&error), xml, tag).unwrap_or(Error::Os(error))
+    }
+}
+
+/// Errors of any other call are kept as they are, without being classified.
+#[cfg(windows)]
+impl From<windows::core::Error> for Error {
+    fn from(error: windows::core::Error) -> Self {
+        Error::Os(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hresult(code: u32) -> i32 {
+        code as i32
+    }
+
+    #[test]
+    fn classifies_app_id_errors() {
+        for code in [ERROR_NOT_FOUND, WPN_E_INVALID_APP] {
+            assert!(matches!(
+                Error::from_hresult(hresult(code)),
+                Error::UnknownAppId { source } if source.code() == hresult(code)
+            ));
+        }
+    }
+
+    #[test]
+    fn classifies_disabled_notifications() {
+        for code in [
+            WPN_E_NOTIFICATION_DISABLED,
+            WPN_E_NOTIFICATION_TYPE_DISABLED,
+        ] {
+            assert!(matches!(
+                Error::from_hresult(hresult(code)),
+                Error::NotificationsDisabled { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn classifies_unsupported() {
+        for code in [
+            REGDB_E_CLASSNOTREG,
+            E_NOTIMPL,
+            E_NOINTERFACE,
+            ERROR_NOT_SUPPORTED,
+        ] {
+            assert!(matches!(
+                Error::from_hresult(hresult(code)),
+                Error::Unsupported { source, .. } if source.code() == hresult(code)
+            ));
+        }
+        assert!(matches!(
+            Error::from_hresult(hresult(WPN_E_PLATFORM_UNAVAILABLE)),
+            Error::BackendUnavailable { source: Some(_) }
+        ));
+    }
+
+    #[test]
+    fn keeps_the_source() {
+        use std::error::Error as _;
+
+        let source = OsError::new(
+            hresult(E_NOINTERFACE),
+            "No such interface supported".to_owned(),
+        );
+        let error = Error::classify(&source).unwrap();
+        assert_eq!(
+            error.to_string(),
+            "not supported by this version of Windows: this operation"
+        );
+        assert_eq!(
+            error.source().unwrap().to_string(),
+            "No such interface supported (HRESULT 0x80004002)"
+        );
+    }
+
+    #[test]
+    fn classifies_refused_toasts() {
+        let refused = |code| match Error::classify_show(
+            &OsError::new(hresult(code), String::new()),
+            "<toast/>",
+            "t!",
+        ) {
+            Some(Error::Violations(violations)) => violations,
+            error => panic!("expected violations, got {error:?}"),
+        };
+        assert_eq!(
+            refused(WPN_E_NOTIFICATION_SIZE),
+            [Violation::PayloadTooLarge { size: 8 }]
+        );
+        assert_eq!(
+            refused(WPN_E_TAG_ALPHANUMERIC),
+            [Violation::InvalidTag {
+                tag: "t!".to_owned(),
+                reason: "must be alphanumeric".to_owned()
+            }]
+        );
+        // other toasts fail with these codes only when shown
+        assert!(matches!(
+            Error::from_hresult(hresult(WPN_E_TAG_SIZE)),
+            Error::ToastFailed(_)
+        ));
+        assert!(matches!(
+            Error::classify_show(
+                &OsError::new(hresult(WPN_E_INVALID_APP), String::new()),
+                "",
+                ""
+            ),
+            Some(Error::UnknownAppId { .. })
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn keeps_other_windows_errors() {
+        // a missing tag in the history
+        let error = windows::core::Error::from(windows::core::HRESULT(hresult(ERROR_NOT_FOUND)));
+        assert!(matches!(Error::from(error.clone()), Error::Os(_)));
+        assert!(matches!(
+            Error::classified(error),
+            Error::UnknownAppId { .. }
+        ));
+    }
+
+    #[test]
+    fn classifies_xml_parser_errors() {
+        let error = Error::from_hresult(hresult(0xC00CE556));
+        assert!(matches!(error, Error::InvalidXml { line: 0, .. }));
+        assert_eq!(
+            error.to_string(),
+            "invalid toast xml: the xml parser failed with 0xC00CE556"
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_codes() {
+        // E_FAIL
+        assert!(matches!(
+            Error::from_hresult(hresult(0x80004005)),
+            Error::ToastFailed(code) if code == hresult(0x80004005)
+        ));
+    }
+
+    #[test]
+    fn formats_xml_position() {
+        let error = Error::InvalidXml {
+            message: "unexpected end of file".to_owned(),
+            line: 3,
+            column: 14,
+            snippet: "<text>".to_owned(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid toast xml at line 3, column 14: unexpected end of file near `<text>`"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use std::fmt::Display;
+
+use crate::{Error, Toast, ToastArguments, ToastDismissalReason};
+
+/// What the user did to activate a toast.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Activation {
+    /// The action of the clicked [button](crate::Toast::add_button) or the arguments of the clicked [header](crate::Header),
+    /// or the [launch](crate::Toast::launch) arguments if the toast itself was clicked, `None` if it has none.
+    pub arguments: Option<String>,
+    /// The part of the toast that was clicked.
+    pub target: ActivationTarget,
+    /// The values of the toast's inputs, keyed by input id.
+    ///
+    /// Always empty before the Windows 10 Fall Creators Update.
+    pub user_input: HashMap<String, String>,
+}
+
+/// The part of a toast the user clicked, see [Activation::target].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActivationTarget {
+    /// The toast itself.
+    #[default]
+    Body,
+    /// One of its buttons.
+    Button,
+    /// Its [header](crate::Header) in the action center.
+    Header,
+}
+
+/// Tells which part of a toast sent the arguments of an activation.
+///
+/// Windows only reports the arguments, so parts sending the same arguments cannot be told apart,
+/// see [Violation::AmbiguousArguments](crate::Violation::AmbiguousArguments).
+pub(crate) struct Targets {
+    buttons: Vec<String>,
+    header: Option<String>,
+}
+
+impl Targets {
+    pub fn of(toast: &Toast) -> Targets {
+        Targets {
+            buttons: toast
+                .buttons
+                .iter()
+                .filter(|b| b.protocol.is_none())
+                .map(|b| b.action.clone())
+                .collect(),
+            header: toast.header.as_ref().map(|header| header.arguments.clone()),
+        }
+    }
+
+    /// Set the [target](Activation::target) of `activation` from its arguments.
+    pub fn classify(&self, activation: &mut Activation) {
+        let arguments = activation.arguments.as_deref().unwrap_or_default();
+        activation.target = if self.buttons.iter().any(|action| action == arguments) {
+            ActivationTarget::Button
+        } else if self.header.as_deref() == Some(arguments) {
+            ActivationTarget::Header
+        } else {
+            ActivationTarget::Body
+        };
+    }
+}
+
+impl Activation {
+    /// The [arguments](Self::arguments) decoded as [ToastArguments], empty if there are none.
+    pub fn toast_arguments(&self) -> ToastArguments {
+        ToastArguments::decode(self.arguments.as_deref().unwrap_or_default())
+    }
+}
+
+/// Windows failed to show a toast, for example because notifications are turned off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToastFailure {
+    code: i32,
+}
+
+impl ToastFailure {
+    // only the windows backend reports failures outside of tests
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn new(code: i32) -> ToastFailure {
+        ToastFailure { code }
+    }
+
+    /// The HRESULT error code Windows reported.
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// The [Error](enum@Error) corresponding to the error code.
+    pub fn error(&self) -> Error {
+        Error::from_hresult(self.code)
+    }
+}
+
+impl Display for ToastFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error())
+    }
+}
+
+impl From<ToastFailure> for Error {
+    fn from(failure: ToastFailure) -> Self {
+        failure.error()
+    }
+}
+
+/// How a toast shown with [Notifier::show_and_wait](crate::Notifier::show_and_wait) ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToastOutcome {
+    /// The user clicked the toast or one of its buttons.
+    Activated(Activation),
+    /// The toast was dismissed, see [Toast::on_dismissed](crate::Toast::on_dismissed) for the possible reasons.
+    Dismissed(Option<ToastDismissalReason>),
+    /// Windows failed to show the toast.
+    Failed(ToastFailure),
+    /// The user did not react before the timeout, the toast might still be on the screen.
+    TimedOut,
+}
+
+/// An event raised by Windows for a shown toast.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToastEvent {
+    /// The user clicked the toast or one of its buttons.
+    Activated(Activation),
+    /// The toast was dismissed, see [Toast::on_dismissed](crate::Toast::on_dismissed) for the possible reasons.
+    Dismissed(Option<ToastDismissalReason>),
+    /// Windows failed to show the toast.
+    Failed(ToastFailure),
+}
+
+impl From<ToastEvent> for ToastOutcome {
+    fn from(event: ToastEvent) -> Self {
+        match event {
+            ToastEvent::Activated(activation) => ToastOutcome::Activated(activation),
+            ToastEvent::Dismissed(reason) => ToastOutcome::Dismissed(reason),
+            ToastEvent::Failed(failure) => ToastOutcome::Failed(failure),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Futures and streams over the events of shown toasts, enabled by the `async` feature.
+//!
+//! None of these depend on a particular async runtime.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_channel::{mpsc, oneshot};
+use futures_core::Stream;
+
+use crate::{Notifier, Result, Toast, ToastEvent, ToastHandle, ToastOutcome};
+
+impl Notifier {
+    /// Display the toast and get a future that resolves once the user reacts to it.
+    ///
+    /// Unlike [Notifier::show_and_wait] this has no timeout, use the timer of your runtime for that.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use tauri_winrt_notification::{Notifier, Toast, ToastOutcome};
+    ///
+    /// # async fn run() -> tauri_winrt_notification::Result<()> {
+    /// let notifier = Notifier::new(Toast::POWERSHELL_APP_ID)?;
+    /// let toast = Toast::new(Toast::POWERSHELL_APP_ID)
+    ///     .title("Install update?")
+    ///     .add_button("Install", "install");
+    ///
+    /// if let ToastOutcome::Activated(activation) = notifier.show_async(&toast)?.await {
+    ///     println!("{:?}", activation.arguments);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn show_async(&self, toast: &Toast) -> Result<ToastFuture> {
+        let (sender, receiver) = oneshot::channel();
+        let mut sender = Some(sender);
+        let handle = self.show_with_listener(
+            toast,
+            Some(Box::new(move |event| {
+                if let Some(sender) = sender.take() {
+                    let _ = sender.send(event);
+                }
+            })),
+        )?;
+
+        Ok(ToastFuture { handle, receiver })
+    }
+
+    /// Display the toast and get a stream of all its events.
+    pub fn show_with_events(&self, toast: &Toast) -> Result<ToastEvents> {
+        let (sender, receiver) = mpsc::unbounded();
+        let handle = self.show_with_listener(
+            toast,
+            Some(Box::new(move |event| {
+                let _ = sender.unbounded_send(event);
+            })),
+        )?;
+
+        Ok(ToastEvents { handle, receiver })
+    }
+
+    /// Get a stream of the tag and events of every toast shown by this notifier from now on.
+    ///
+    /// The stream only sees events of toasts that are still alive, see [ToastHandle].
+    pub fn events(&self) -> NotifierEvents {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribe(Box::new(move |tag, event| {
+            sender
+                .unbounded_send((tag.to_owned(), event.clone()))
+                .is_ok()
+        }));
+
+        NotifierEvents { receiver }
+    }
+}
+
+impl Toast {
+    /// Display the toast and get a future that resolves once the user reacts to it.
+    ///
+    /// See [Notifier::show_async].
+    pub fn show_async(&self) -> Result<ToastFuture> {
+        self.notifier()?.show_async(self)
+    }
+}
+
+/// Resolves to the [ToastOutcome] of a toast shown with [Notifier::show_async].
+///
+/// Never resolves to [ToastOutcome::TimedOut], unless Windows drops the toast without telling.
+pub struct ToastFuture {
+    handle: ToastHandle,
+    receiver: oneshot::Receiver<ToastEvent>,
+}
+
+impl ToastFuture {
+    /// The handle of the shown toast.
+    pub fn handle(&self) -> &ToastHandle {
+        &self.handle
+    }
+}
+
+impl Future for ToastFuture {
+    type Output = ToastOutcome;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.receiver)
+            .poll(cx)
+            .map(|event| event.map_or(ToastOutcome::TimedOut, Into::into))
+    }
+}
+
+/// The events of a toast shown with [Notifier::show_with_events].
+///
+/// A toast can still be activated from the action center after it was dismissed,
+/// so the stream does not end on its own, drop it when you are no longer interested.
+pub struct ToastEvents {
+    handle: ToastHandle,
+    receiver: mpsc::UnboundedReceiver<ToastEvent>,
+}
+
+impl ToastEvents {
+    /// The handle of the shown toast.
+    pub fn handle(&self) -> &ToastHandle {
+        &self.handle
+    }
+}
+
+impl Stream for ToastEvents {
+    type Item = ToastEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+/// The tag and events of every toast shown by a notifier, see [Notifier::events].
+pub struct NotifierEvents {
+    receiver: mpsc::UnboundedReceiver<(String, ToastEvent)>,
+}
+
+impl Stream for NotifierEvents {
+    type Item = (String, ToastEvent);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::{executor::block_on, StreamExt};
+
+    use super::*;
+    use crate::backend::simulated::SimulatedBackend;
+    use crate::{Activation, ToastDismissalReason, ToastFailure};
+
+    fn notifier() -> (Notifier, SimulatedBackend) {
+        let backend = SimulatedBackend::default();
+        let notifier = Notifier::with_backend(Toast::POWERSHELL_APP_ID, Arc::new(backend.clone()));
+        (notifier, backend)
+    }
+
+    fn activated(arguments: &str) -> ToastEvent {
+        ToastEvent::Activated(Activation {
+            arguments: Some(arguments.to_owned()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn show_async_resolves_to_outcome() {
+        let (notifier, backend) = notifier();
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).tag("update");
+
+        let future = notifier.show_async(&toast).unwrap();
+        assert_eq!(future.handle().tag(), "update");
+        backend.raise("update", activated("install"));
+        // later events don't change the outcome
+        backend.raise("update", ToastEvent::Dismissed(None));
+
+        assert_eq!(
+            block_on(future),
+            ToastOutcome::Activated(Activation {
+                arguments: Some("install".to_owned()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn show_with_events_streams_every_event() {
+        let (notifier, backend) = notifier();
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).tag("chat");
+
+        let mut events = notifier.show_with_events(&toast).unwrap();
+        backend.raise(
+            "chat",
+            ToastEvent::Dismissed(Some(ToastDismissalReason::TimedOut)),
+        );
+        backend.raise("chat", activated("reply"));
+
+        block_on(async {
+            assert_eq!(
+                events.next().await,
+                Some(ToastEvent::Dismissed(Some(ToastDismissalReason::TimedOut)))
+            );
+            assert_eq!(events.next().await, Some(activated("reply")));
+        });
+    }
+
+    #[test]
+    fn notifier_events_cover_all_toasts() {
+        let (notifier, backend) = notifier();
+        let mut events = notifier.events();
+
+        let _first = notifier
+            .show(&Toast::new(Toast::POWERSHELL_APP_ID).tag("first"))
+            .unwrap();
+        let _second = notifier
+            .show(&Toast::new(Toast::POWERSHELL_APP_ID).tag("second"))
+            .unwrap();
+        backend.raise("second", activated("b"));
+        backend.raise("first", ToastEvent::Failed(ToastFailure::new(-1)));
+
+        block_on(async {
+            assert_eq!(
+                events.next().await,
+                Some(("second".to_owned(), activated("b")))
+            );
+            assert_eq!(
+                events.next().await,
+                Some((
+                    "first".to_owned(),
+                    ToastEvent::Failed(ToastFailure::new(-1))
+                ))
+            );
+        });
+    }
+}
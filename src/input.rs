@@ -0,0 +1,159 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt::Write;
+
+use quick_xml::escape::escape;
+
+/// An input field shown above the buttons of a toast.
+///
+/// The value the user entered is reported by id in [Activation::user_input](crate::Activation::user_input).
+/// Inputs require the Windows 10 Anniversary Update.
+///
+/// # Example
+/// ```rust,no_run
+/// use tauri_winrt_notification::{Input, Toast};
+///
+/// Toast::new(Toast::POWERSHELL_APP_ID)
+///     .title("Lunch?")
+///     .add_input(Input::selection("place", &[("pizza", "Pizza"), ("sushi", "Sushi")]).default_value("sushi"))
+///     .add_input(Input::text("comment").placeholder("Anything else?"))
+///     .add_button("Send", "send")
+///     .show()
+///     .expect("notification failed");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Input {
+    id: String,
+    kind: InputKind,
+    title: Option<String>,
+    placeholder: Option<String>,
+    default_value: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum InputKind {
+    Text,
+    /// `(id, content)` of every item.
+    Selection(Vec<(String, String)>),
+}
+
+impl Input {
+    /// A text box.
+    pub fn text(id: &str) -> Input {
+        Input::new(id, InputKind::Text)
+    }
+
+    /// A drop down list of `(id, content)` items, the id of the picked item is reported.
+    pub fn selection(id: &str, items: &[(&str, &str)]) -> Input {
+        let items = items
+            .iter()
+            .map(|(id, content)| (id.to_string(), content.to_string()))
+            .collect();
+        Input::new(id, InputKind::Selection(items))
+    }
+
+    fn new(id: &str, kind: InputKind) -> Input {
+        Input {
+            id: id.to_owned(),
+            kind,
+            title: None,
+            placeholder: None,
+            default_value: None,
+        }
+    }
+
+    /// Set the text shown above the input.
+    pub fn title(mut self, title: &str) -> Input {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    /// Set the text shown in an empty text box.
+    pub fn placeholder(mut self, placeholder: &str) -> Input {
+        self.placeholder = Some(placeholder.to_owned());
+        self
+    }
+
+    /// Set the initial text of a text box, or the id of the initially picked item of a selection.
+    pub fn default_value(mut self, value: &str) -> Input {
+        self.default_value = Some(value.to_owned());
+        self
+    }
+
+    /// The id the value of this input is reported with.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The number of items of a selection, 0 for a text box.
+    pub(crate) fn item_count(&self) -> usize {
+        match &self.kind {
+            InputKind::Text => 0,
+            InputKind::Selection(items) => items.len(),
+        }
+    }
+
+    pub(crate) fn xml(&self) -> String {
+        let mut xml = format!(r#"<input id="{}""#, escape(&self.id));
+        let _ = match self.kind {
+            InputKind::Text => write!(xml, r#" type="text""#),
+            InputKind::Selection(_) => write!(xml, r#" type="selection""#),
+        };
+        for (name, value) in [
+            ("title", &self.title),
+            ("placeHolderContent", &self.placeholder),
+            ("defaultInput", &self.default_value),
+        ] {
+            if let Some(value) = value {
+                let _ = write!(xml, r#" {}="{}""#, name, escape(value));
+            }
+        }
+
+        match &self.kind {
+            InputKind::Text => xml.push_str("/>"),
+            InputKind::Selection(items) => {
+                xml.push('>');
+                for (id, content) in items {
+                    let _ = write!(
+                        xml,
+                        r#"<selection id="{}" content="{}"/>"#,
+                        escape(id),
+                        escape(content)
+                    );
+                }
+                xml.push_str("</input>");
+            }
+        }
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_xml() {
+        let input = Input::text("reply")
+            .placeholder("Say \"hi\"")
+            .title("Reply");
+        assert_eq!(
+            input.xml(),
+            r#"<input id="reply" type="text" title="Reply" placeHolderContent="Say &quot;hi&quot;"/>"#
+        );
+    }
+
+    #[test]
+    fn selection_xml() {
+        let input =
+            Input::selection("size", &[("s", "Small"), ("l", "Large & more")]).default_value("l");
+        assert_eq!(input.item_count(), 2);
+        assert_eq!(
+            input.xml(),
+            r#"<input id="size" type="selection" defaultInput="l"><selection id="s" content="Small"/><selection id="l" content="Large &amp; more"/></input>"#
+        );
+    }
+}
@@ -19,6 +19,8 @@
 //!
 //! * Windows 8.1 only supports a single image, the last image (icon, hero, image) will be the one on the toast
 //!
+//! Toasts are rendered for a [TargetProfile], which decides how content the running version of Windows lacks is downgraded.
+//!
 //! for xml schema details check out:
 //!
 //! * <https://docs.microsoft.com/en-us/uwp/schemas/tiles/toastschema/root-elements>
@@ -31,61 +33,131 @@
 //! * <https://softwareengineering.stackexchange.com/questions/222339/using-the-system-tray-notification-area-app-in-windows-7>
 //!
 //! For actions look at <https://docs.microsoft.com/en-us/dotnet/api/microsoft.toolkit.uwp.notifications.toastactionscustom?view=win-comm-toolkit-dotnet-7.0>
-use windows::{
-    core::{IInspectable, Interface},
-    Data::Xml::Dom::XmlDocument,
-    Foundation::{Collections::StringMap, TypedEventHandler},
-    UI::Notifications::{
-        NotificationData, ToastActivatedEventArgs, ToastDismissedEventArgs,
-        ToastNotificationManager,
-    },
-};
-
+//!
+//! Cargo features:
+//!
+//! * `async`: [Notifier::show_async] and streams of toast events, independent of any async runtime.
+//! * `image-processing`: [ImageProcessor], resizing, cropping and converting images so they look the same everywhere.
+//! * `remote-images`: [RemoteImages], downloading `http(s)` images that Windows does not load for unpackaged apps.
+//! * `serde`: turning structs into [ToastArguments] and back.
 use std::fmt::Display;
-use std::fmt::Write;
-use std::path::Path;
-use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration as StdDuration;
 
+#[cfg(windows)]
 pub use windows::core::HSTRING;
-pub use windows::UI::Notifications::NotificationUpdateResult;
+#[cfg(windows)]
 pub use windows::UI::Notifications::ToastNotification;
 
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-pub enum Error {
-    #[error("Windows API error: {0}")]
-    Os(#[from] windows::core::Error),
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+mod arguments;
+mod backend;
+mod cache;
+mod call;
+mod dimensions;
+mod error;
+mod event;
+#[cfg(feature = "async")]
+mod future;
+mod input;
+mod lint;
+mod notifier;
+pub mod presets;
+#[cfg(feature = "image-processing")]
+mod process;
+mod profile;
+mod protocol;
+#[cfg(feature = "remote-images")]
+mod remote;
+mod render;
+mod sound;
+mod source;
+mod validate;
+mod xml;
+
+pub use arguments::ToastArguments;
+pub use cache::ImageCache;
+pub use call::IncomingCall;
+pub use error::{Error, OsError, Result};
+pub use event::{Activation, ActivationTarget, ToastEvent, ToastFailure, ToastOutcome};
+#[cfg(feature = "async")]
+pub use future::{NotifierEvents, ToastEvents, ToastFuture};
+pub use input::Input;
+pub use lint::{Lint, LintCode, LintCodeParsingError};
+pub use notifier::{History, HistoryEntry, Notifier, ToastHandle};
+#[cfg(feature = "image-processing")]
+pub use process::ImageProcessor;
+pub use profile::{Capability, TargetProfile};
+pub use protocol::{ProtocolUri, MAX_PROTOCOL_URI_LENGTH};
+#[cfg(feature = "remote-images")]
+pub use remote::RemoteImages;
+pub use render::{Downgrade, Rendered};
+pub use sound::{LoopPolicy, LoopableSound, Sound, SoundParsingError};
+pub use source::{AudioSource, ImageSource};
+pub use validate::{Severity, Violation};
+
+/// `ToastDismissalReason` is an enum representing the reason a toast notification was dismissed.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToastDismissalReason {
+    /// The user explicitly dismissed the toast notification.
+    UserCanceled,
+    /// The application hid the toast notification programmatically.
+    ApplicationHidden,
+    /// The toast notification was dismissed because it timed out.
+    TimedOut,
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+/// The result of updating the data of a shown toast, see [Toast::set_progress].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationUpdateResult {
+    /// The toast was updated.
+    Succeeded,
+    /// The toast could not be updated.
+    Failed,
+    /// No toast with the given tag is on the screen or in the action center.
+    NotificationNotFound,
+}
 
-/// `ToastDismissalReason` is a struct representing the reason a toast notification was dismissed.
-///
-/// Variants:
-/// - `UserCanceled`: The user explicitly dismissed the toast notification.
-/// - `ApplicationHidden`: The application hid the toast notification programmatically.
-/// - `TimedOut`: The toast notification was dismissed because it timed out.
-pub use windows::UI::Notifications::ToastDismissalReason;
+type ActivatedHandler = dyn FnMut(Option<String>) -> Result<()> + Send;
+type ActivationHandler = dyn FnMut(Activation) -> Result<()> + Send;
+type DismissedHandler = dyn FnMut(Option<ToastDismissalReason>) -> Result<()> + Send;
+type FailedHandler = dyn FnMut(Error) -> Result<()> + Send;
 
+#[derive(Clone)]
 pub struct Toast {
-    duration: String,
-    title: String,
-    line1: String,
-    line2: String,
-    images: String,
-    audio: String,
-    app_id: String,
+    duration: Option<Duration>,
+    title: Option<String>,
+    line1: Option<String>,
+    line2: Option<String>,
+    images: Vec<Image>,
+    /// `None` if the toast is silent.
+    sound: Option<Sound>,
+    loop_policy: LoopPolicy,
+    /// `None` for toasts only shown through a [Notifier].
+    app_id: Option<String>,
+    tag: Option<String>,
     progress: Option<Progress>,
-    scenario: String,
-    on_activated: Option<TypedEventHandler<ToastNotification, IInspectable>>,
-    on_dismissed: Option<TypedEventHandler<ToastNotification, ToastDismissedEventArgs>>,
+    scenario: Scenario,
+    inputs: Vec<Input>,
+    allowed_lints: Vec<LintCode>,
+    on_activated: Option<Arc<Mutex<ActivatedHandler>>>,
+    on_activation: Option<Arc<Mutex<ActivationHandler>>>,
+    on_dismissed: Option<Arc<Mutex<DismissedHandler>>>,
+    on_failed: Option<Arc<Mutex<FailedHandler>>>,
     buttons: Vec<Button>,
+    header: Option<Header>,
+    legacy_template: Option<LegacyTemplate>,
+    visual_images: ImageDefaults,
+    binding_images: ImageDefaults,
+    center_call_texts: bool,
+    launch: Option<String>,
+    activation_type: Option<ActivationType>,
+    /// The package family name of the app opening the [launch protocol](Toast::launch_protocol).
+    launch_target: Option<String>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Duration {
     /// 7 seconds
     Short,
@@ -94,177 +166,111 @@ pub enum Duration {
     Long,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Sound {
-    Default,
-    IM,
-    Mail,
-    Reminder,
-    SMS,
-    /// Play the loopable sound only once
-    Single(LoopableSound),
-    /// Loop the loopable sound for the entire duration of the toast
-    Loop(LoopableSound),
-}
-
-impl TryFrom<&str> for Sound {
-    type Error = SoundParsingError;
-
-    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
-        Self::from_str(value)
-    }
+#[derive(Clone)]
+struct Button {
+    content: String,
+    action: String,
+    style: Option<ButtonStyle>,
+    /// `Some` if the button opens `action` as a URI, with the package family name of the app opening it.
+    protocol: Option<Option<String>>,
 }
 
-impl FromStr for Sound {
-    type Err = SoundParsingError;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        Ok(match s {
-            "Default" => Sound::Default,
-            "IM" => Sound::IM,
-            "Mail" => Sound::Mail,
-            "Reminder" => Sound::Reminder,
-            "SMS" => Sound::SMS,
-            _ => Sound::Single(LoopableSound::from_str(s)?),
-        })
-    }
+/// The color of a button, see [Toast::add_styled_button].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonStyle {
+    /// Green, for positive actions like accepting a call.
+    Success,
+    /// Red, for destructive actions like declining a call.
+    Critical,
 }
 
-impl Display for Sound {
+impl Display for ButtonStyle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "{}",
-            match &self {
-                Sound::Default => "Default",
-                Sound::IM => "IM",
-                Sound::Mail => "Mail",
-                Sound::Reminder => "Reminder",
-                Sound::SMS => "SMS",
-                Sound::Single(s) | Sound::Loop(s) => return write!(f, "{s}"),
+            match self {
+                ButtonStyle::Success => "Success",
+                ButtonStyle::Critical => "Critical",
             }
         )
     }
 }
 
-struct Button {
-    content: String,
-    action: String,
+#[derive(Clone)]
+struct Image {
+    placement: ImagePlacement,
+    source: ImageSource,
+    alt_text: String,
+    options: ImageOptions,
 }
 
-/// Sounds suitable for Looping
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub enum LoopableSound {
-    Alarm,
-    Alarm2,
-    Alarm3,
-    Alarm4,
-    Alarm5,
-    Alarm6,
-    Alarm7,
-    Alarm8,
-    Alarm9,
-    Alarm10,
-    Call,
-    Call2,
-    Call3,
-    Call4,
-    Call5,
-    Call6,
-    Call7,
-    Call8,
-    Call9,
-    Call10,
+#[derive(Clone)]
+enum ImagePlacement {
+    AppLogo(IconCrop),
+    Hero,
+    Inline,
 }
 
-#[derive(Debug)]
-pub struct SoundParsingError;
-impl Display for SoundParsingError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "couldn't parse string as a valid sound")
-    }
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconCrop {
+    Square,
+    Circular,
 }
-impl std::error::Error for SoundParsingError {}
 
-impl TryFrom<&str> for LoopableSound {
-    type Error = SoundParsingError;
+/// Image attributes of the `<visual>` or `<binding>` element of a toast, inherited by its images.
+///
+/// Attributes that are `None` are inherited from the element above, see [Toast::image_defaults].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageDefaults {
+    /// Let Windows append the scale factor, contrast and language of the system to image URIs,
+    /// as in `?ms-scale=200&ms-contrast=high&ms-lang=en-US`, so a server can send a matching variant.
+    pub add_image_query: Option<bool>,
+    /// The URI that [relative](ImageSource::relative) image sources are resolved against,
+    /// `ms-appx:///` if not set anywhere.
+    pub base_uri: Option<String>,
+}
 
-    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
-        Self::from_str(value)
-    }
+/// Attributes of a single image, see [Toast::image_with].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageOptions {
+    /// Overrides [ImageDefaults::add_image_query] for this image.
+    pub add_image_query: Option<bool>,
+    /// Remove the 8 pixel margin Windows puts around images inside groups.
+    pub remove_margin: bool,
+    /// How the image is aligned inside a group.
+    pub align: Option<ImageAlign>,
+    /// Crop the image to a circle, like the caller image of an [incoming call](Scenario::IncomingCall).
+    pub circle_crop: bool,
 }
 
-impl FromStr for LoopableSound {
-    type Err = SoundParsingError;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        Ok(match s {
-            "Alarm" => LoopableSound::Alarm,
-            "Alarm2" => LoopableSound::Alarm2,
-            "Alarm3" => LoopableSound::Alarm3,
-            "Alarm4" => LoopableSound::Alarm4,
-            "Alarm5" => LoopableSound::Alarm5,
-            "Alarm6" => LoopableSound::Alarm6,
-            "Alarm7" => LoopableSound::Alarm7,
-            "Alarm8" => LoopableSound::Alarm8,
-            "Alarm9" => LoopableSound::Alarm9,
-            "Alarm10" => LoopableSound::Alarm10,
-            "Call" => LoopableSound::Call,
-            "Call2" => LoopableSound::Call2,
-            "Call3" => LoopableSound::Call3,
-            "Call4" => LoopableSound::Call4,
-            "Call5" => LoopableSound::Call5,
-            "Call6" => LoopableSound::Call6,
-            "Call7" => LoopableSound::Call7,
-            "Call8" => LoopableSound::Call8,
-            "Call9" => LoopableSound::Call9,
-            "Call10" => LoopableSound::Call10,
-            _ => return Err(SoundParsingError),
-        })
-    }
+/// The horizontal alignment of an image, see [ImageOptions::align].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageAlign {
+    Stretch,
+    Left,
+    Center,
+    Right,
 }
 
-impl Display for LoopableSound {
+impl Display for ImageAlign {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "{}",
             match self {
-                LoopableSound::Alarm => "Alarm",
-                LoopableSound::Alarm2 => "Alarm2",
-                LoopableSound::Alarm3 => "Alarm3",
-                LoopableSound::Alarm4 => "Alarm4",
-                LoopableSound::Alarm5 => "Alarm5",
-                LoopableSound::Alarm6 => "Alarm6",
-                LoopableSound::Alarm7 => "Alarm7",
-                LoopableSound::Alarm8 => "Alarm8",
-                LoopableSound::Alarm9 => "Alarm9",
-                LoopableSound::Alarm10 => "Alarm10",
-                LoopableSound::Call => "Call",
-                LoopableSound::Call2 => "Call2",
-                LoopableSound::Call3 => "Call3",
-                LoopableSound::Call4 => "Call4",
-                LoopableSound::Call5 => "Call5",
-                LoopableSound::Call6 => "Call6",
-                LoopableSound::Call7 => "Call7",
-                LoopableSound::Call8 => "Call8",
-                LoopableSound::Call9 => "Call9",
-                LoopableSound::Call10 => "Call10",
+                ImageAlign::Stretch => "stretch",
+                ImageAlign::Left => "left",
+                ImageAlign::Center => "center",
+                ImageAlign::Right => "right",
             }
         )
     }
 }
 
 #[allow(dead_code)]
-#[derive(Clone, Copy)]
-pub enum IconCrop {
-    Square,
-    Circular,
-}
-
-#[allow(dead_code)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Scenario {
     /// The normal toast behavior.
     Default,
@@ -273,7 +279,123 @@ pub enum Scenario {
     /// This will be displayed pre-expanded and stay on the user's screen till dismissed.
     Reminder,
     /// This will be displayed pre-expanded in a special call format and stay on the user's screen till dismissed. Audio will loop by default and will use ringtone audio.
+    ///
+    /// See [IncomingCall] for building such a toast.
     IncomingCall,
+    /// This will break through Do Not Disturb, if the user allows the app to send important notifications.
+    ///
+    /// Requires the Windows 11 2022 Update, older versions show a regular toast.
+    Urgent,
+}
+
+/// How the app is activated when the toast itself is clicked, see [Toast::activation_type].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationType {
+    /// Bring the app to the foreground, the default.
+    Foreground,
+    /// Activate the app without bringing it to the foreground.
+    ///
+    /// Only packaged apps with a background task are activated in the background,
+    /// other apps receive the activation like a foreground one.
+    Background,
+    /// Open the URI in the [launch](Toast::launch) arguments with the app registered for its scheme,
+    /// see [Toast::launch_protocol].
+    Protocol,
+}
+
+impl Display for ActivationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ActivationType::Foreground => "foreground",
+                ActivationType::Background => "background",
+                ActivationType::Protocol => "protocol",
+            }
+        )
+    }
+}
+
+/// The templates of Windows 8.1, which has no adaptive `ToastGeneric` template.
+///
+/// The title, [text1](Toast::text1) and [text2](Toast::text2) fill the text lines of the template in that order,
+/// see <https://learn.microsoft.com/en-us/previous-versions/windows/apps/hh761494(v=win.10)> for how each template looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LegacyTemplate {
+    /// One string wrapped across three lines.
+    ToastText01,
+    /// A bold string on the first line, one string wrapped across the second and third.
+    ToastText02,
+    /// A bold string wrapped across the first and second lines, one string on the third.
+    ToastText03,
+    /// A bold string on the first line, one string on each of the second and third.
+    ToastText04,
+    /// An image and one string wrapped across three lines.
+    ToastImageAndText01,
+    /// An image, a bold string on the first line and one string wrapped across the second and third.
+    ToastImageAndText02,
+    /// An image, a bold string wrapped across the first and second lines and one string on the third.
+    ToastImageAndText03,
+    /// An image, a bold string on the first line and one string on each of the second and third.
+    ToastImageAndText04,
+}
+
+impl LegacyTemplate {
+    /// The name of the template in the toast xml.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LegacyTemplate::ToastText01 => "ToastText01",
+            LegacyTemplate::ToastText02 => "ToastText02",
+            LegacyTemplate::ToastText03 => "ToastText03",
+            LegacyTemplate::ToastText04 => "ToastText04",
+            LegacyTemplate::ToastImageAndText01 => "ToastImageAndText01",
+            LegacyTemplate::ToastImageAndText02 => "ToastImageAndText02",
+            LegacyTemplate::ToastImageAndText03 => "ToastImageAndText03",
+            LegacyTemplate::ToastImageAndText04 => "ToastImageAndText04",
+        }
+    }
+
+    /// How many strings the template shows.
+    pub fn text_count(&self) -> usize {
+        match self {
+            LegacyTemplate::ToastText01 | LegacyTemplate::ToastImageAndText01 => 1,
+            LegacyTemplate::ToastText02
+            | LegacyTemplate::ToastText03
+            | LegacyTemplate::ToastImageAndText02
+            | LegacyTemplate::ToastImageAndText03 => 2,
+            LegacyTemplate::ToastText04 | LegacyTemplate::ToastImageAndText04 => 3,
+        }
+    }
+
+    /// Whether the template shows an image.
+    pub fn has_image(&self) -> bool {
+        matches!(
+            self,
+            LegacyTemplate::ToastImageAndText01
+                | LegacyTemplate::ToastImageAndText02
+                | LegacyTemplate::ToastImageAndText03
+                | LegacyTemplate::ToastImageAndText04
+        )
+    }
+
+    /// The template for a toast with `texts` strings, with or without an image.
+    pub(crate) fn fitting(texts: usize, image: bool) -> LegacyTemplate {
+        match (texts, image) {
+            (0 | 1, false) => LegacyTemplate::ToastText01,
+            (2, false) => LegacyTemplate::ToastText02,
+            (_, false) => LegacyTemplate::ToastText04,
+            (0 | 1, true) => LegacyTemplate::ToastImageAndText01,
+            (2, true) => LegacyTemplate::ToastImageAndText02,
+            (_, true) => LegacyTemplate::ToastImageAndText04,
+        }
+    }
+}
+
+impl Display for LegacyTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
 #[derive(Clone)]
@@ -290,33 +412,61 @@ pub struct Progress {
     pub value_string: String,
 }
 
+/// Groups toasts under a header in the action center.
+///
+/// Headers require the Windows 10 Creators Update.
+#[derive(Debug, Clone)]
+pub struct Header {
+    /// Identifies the header, toasts with the same id are grouped under the same header.
+    pub id: String,
+    /// The text of the header.
+    pub title: String,
+    /// Sent as an argument [on_activated](Toast::on_activated) when the header is clicked.
+    pub arguments: String,
+}
+
 impl Progress {
     fn xml() -> &'static str {
-        r#"<progress
-                title="{progressTitle}"
-                value="{progressValue}"
-                valueStringOverride="{progressValueString}"
-                status="{progressStatus}"/>"#
-    }
-
-    fn tag(&self) -> HSTRING {
-        HSTRING::from(&self.tag)
-    }
-
-    fn title(&self) -> HSTRING {
-        HSTRING::from(&self.title)
-    }
-
-    fn status(&self) -> HSTRING {
-        HSTRING::from(&self.status)
-    }
-
-    fn value(&self) -> HSTRING {
-        HSTRING::from(&self.value.to_string())
+        concat!(
+            r#"<progress title="{progressTitle}" value="{progressValue}" "#,
+            r#"valueStringOverride="{progressValueString}" status="{progressStatus}"/>"#
+        )
     }
+}
 
-    fn value_string(&self) -> HSTRING {
-        HSTRING::from(&self.value_string)
+impl Default for Toast {
+    /// A toast without an app id, pure content to be shown through a [Notifier].
+    ///
+    /// [Toast::show], [Toast::show_and_wait] and [Toast::set_progress] fail with [Error::MissingAppId] for such toasts.
+    fn default() -> Toast {
+        Toast {
+            duration: None,
+            title: None,
+            line1: None,
+            line2: None,
+            images: Vec::new(),
+            sound: Some(Sound::Default),
+            loop_policy: LoopPolicy::Warn,
+            app_id: None,
+            tag: None,
+            progress: None,
+            scenario: Scenario::Default,
+            inputs: Vec::new(),
+            allowed_lints: Vec::new(),
+            on_activated: None,
+            on_activation: None,
+            on_dismissed: None,
+            on_failed: None,
+            buttons: Vec::new(),
+            header: None,
+            legacy_template: None,
+            visual_images: ImageDefaults::default(),
+            binding_images: ImageDefaults::default(),
+            center_call_texts: false,
+            launch: None,
+            activation_type: None,
+            launch_target: None,
+        }
     }
 }
 
@@ -328,7 +478,10 @@ impl Toast {
                                                  \\WindowsPowerShell\\v1.0\\powershell.exe";
     /// Constructor for the toast builder.
     ///
-    /// app_id is the running application's [AppUserModelID][1].
+    /// app_id is the running application's [AppUserModelID][1], used by [show](Self::show),
+    /// [show_and_wait](Self::show_and_wait) and [set_progress](Self::set_progress).
+    /// A [Notifier] shows toasts under its own app id and ignores this one,
+    /// so toasts that are only sent through a notifier can be created with [Toast::default] instead.
     ///
     /// [1]: https://msdn.microsoft.com/en-us/library/windows/desktop/dd378459(v=vs.85).aspx
     ///
@@ -336,18 +489,8 @@ impl Toast {
     #[allow(dead_code)]
     pub fn new(app_id: &str) -> Toast {
         Toast {
-            duration: String::new(),
-            title: String::new(),
-            line1: String::new(),
-            line2: String::new(),
-            images: String::new(),
-            audio: String::new(),
-            app_id: app_id.to_string(),
-            progress: None,
-            scenario: String::new(),
-            on_activated: None,
-            on_dismissed: None,
-            buttons: Vec::new(),
+            app_id: Some(app_id.to_owned()),
+            ..Toast::default()
         }
     }
 
@@ -356,10 +499,7 @@ impl Toast {
     /// Will be white.
     /// Supports Unicode ✓
     pub fn title(mut self, content: &str) -> Toast {
-        self.title = format!(
-            r#"<text id="1">{}</text>"#,
-            &quick_xml::escape::escape(content)
-        );
+        self.title = Some(content.to_owned());
         self
     }
 
@@ -368,10 +508,7 @@ impl Toast {
     /// Will be grey.
     /// Supports Unicode ✓
     pub fn text1(mut self, content: &str) -> Toast {
-        self.line1 = format!(
-            r#"<text id="2">{}</text>"#,
-            &quick_xml::escape::escape(content)
-        );
+        self.line1 = Some(content.to_owned());
         self
     }
 
@@ -380,20 +517,13 @@ impl Toast {
     /// Will be grey.
     /// Supports Unicode ✓
     pub fn text2(mut self, content: &str) -> Toast {
-        self.line2 = format!(
-            r#"<text id="3">{}</text>"#,
-            &quick_xml::escape::escape(content)
-        );
+        self.line2 = Some(content.to_owned());
         self
     }
 
     /// Set the length of time to show the toast
     pub fn duration(mut self, duration: Duration) -> Toast {
-        self.duration = match duration {
-            Duration::Long => "duration=\"long\"",
-            Duration::Short => "duration=\"short\"",
-        }
-        .to_string();
+        self.duration = Some(duration);
         self
     }
 
@@ -402,13 +532,43 @@ impl Toast {
     /// The system keeps the notification on screen until the user acts upon/dismisses it.
     /// The system also plays the suitable notification sound as well.
     pub fn scenario(mut self, scenario: Scenario) -> Toast {
-        self.scenario = match scenario {
-            Scenario::Default => "",
-            Scenario::Alarm => "scenario=\"alarm\"",
-            Scenario::Reminder => "scenario=\"reminder\"",
-            Scenario::IncomingCall => "scenario=\"incomingCall\"",
-        }
-        .to_string();
+        self.scenario = scenario;
+        self
+    }
+
+    /// Set the arguments sent when the toast itself is clicked, instead of one of its buttons.
+    ///
+    /// The activation reports them with [ActivationTarget::Body], see [ToastArguments] for structured arguments.
+    /// Without launch arguments, clicking the toast sends `None`.
+    pub fn launch(mut self, arguments: &str) -> Toast {
+        self.launch = Some(arguments.to_owned());
+        self
+    }
+
+    /// Set how the app is activated when the toast itself is clicked.
+    ///
+    /// Default is [ActivationType::Foreground]. Requires Windows 10, Windows 8.1 always activates in the foreground.
+    pub fn activation_type(mut self, activation_type: ActivationType) -> Toast {
+        self.activation_type = Some(activation_type);
+        self
+    }
+
+    /// Open `uri` when the toast itself is clicked, instead of activating the app.
+    ///
+    /// Sets the [launch](Self::launch) arguments to the URI and the [activation type](Self::activation_type)
+    /// to [ActivationType::Protocol]. Requires Windows 10, Windows 8.1 activates the app with the URI instead.
+    pub fn launch_protocol(mut self, uri: &ProtocolUri) -> Toast {
+        self.launch = Some(uri.as_str().to_owned());
+        self.activation_type = Some(ActivationType::Protocol);
+        self.launch_target = uri.target().map(str::to_owned);
+        self
+    }
+
+    /// Center the texts of an [incoming call](Scenario::IncomingCall), as phone apps do.
+    ///
+    /// Has no effect with other scenarios or before Windows 11.
+    pub fn center_call_texts(mut self, center: bool) -> Toast {
+        self.center_call_texts = center;
         self
     }
 
@@ -416,60 +576,71 @@ impl Toast {
     ///
     /// The default is determined by your app id.
     /// If you are using the powershell workaround, it will be the powershell icon
-    pub fn icon(mut self, source: &Path, crop: IconCrop, alt_text: &str) -> Toast {
-        if is_newer_than_windows81() {
-            let crop_type_attr = match crop {
-                IconCrop::Square => "".to_string(),
-                IconCrop::Circular => "hint-crop=\"circle\"".to_string(),
-            };
-
-            self.images = format!(
-                r#"{}<image placement="appLogoOverride" {} src="file:///{}" alt="{}" />"#,
-                self.images,
-                crop_type_attr,
-                quick_xml::escape::escape(&source.display().to_string()),
-                quick_xml::escape::escape(alt_text)
-            );
-            self
-        } else {
-            // Win81 rejects the above xml, so we fall back to a simpler call
-            self.image(source, alt_text)
-        }
+    ///
+    /// Windows 8.1 shows the icon as a regular [image](Self::image).
+    pub fn icon(mut self, source: impl Into<ImageSource>, crop: IconCrop, alt_text: &str) -> Toast {
+        self.images.push(Image {
+            placement: ImagePlacement::AppLogo(crop),
+            source: source.into(),
+            alt_text: alt_text.to_owned(),
+            options: ImageOptions::default(),
+        });
+        self
     }
 
     /// Add/Set a Hero image for the toast.
     ///
     /// This will be above the toast text and the icon.
-    pub fn hero(mut self, source: &Path, alt_text: &str) -> Toast {
-        if is_newer_than_windows81() {
-            self.images = format!(
-                r#"{}<image placement="Hero" src="file:///{}" alt="{}" />"#,
-                self.images,
-                quick_xml::escape::escape(&source.display().to_string()),
-                quick_xml::escape::escape(alt_text)
-            );
-            self
-        } else {
-            // win81 rejects the above xml, so we fall back to a simpler call
-            self.image(source, alt_text)
-        }
+    /// Versions before the Anniversary Update show it as a regular [image](Self::image).
+    pub fn hero(mut self, source: impl Into<ImageSource>, alt_text: &str) -> Toast {
+        self.images.push(Image {
+            placement: ImagePlacement::Hero,
+            source: source.into(),
+            alt_text: alt_text.to_owned(),
+            options: ImageOptions::default(),
+        });
+        self
     }
 
     /// Add an image to the toast
     ///
     /// May be done many times.
     /// Will appear below text.
-    pub fn image(mut self, source: &Path, alt_text: &str) -> Toast {
-        if !is_newer_than_windows81() {
-            // win81 cannot have more than 1 image and shows nothing if there is more than that
-            self.images = String::new();
-        }
-        self.images = format!(
-            r#"{}<image id="1" src="file:///{}" alt="{}" />"#,
-            self.images,
-            quick_xml::escape::escape(&source.display().to_string()),
-            quick_xml::escape::escape(alt_text)
-        );
+    /// Windows 8.1 only shows the last image of the toast.
+    ///
+    /// `source` is a local path or an [ImageSource], the same goes for [icon](Self::icon) and [hero](Self::hero).
+    pub fn image(self, source: impl Into<ImageSource>, alt_text: &str) -> Toast {
+        self.image_with(source, alt_text, ImageOptions::default())
+    }
+
+    /// Add an [image](Self::image) with attributes of its own.
+    pub fn image_with(
+        mut self,
+        source: impl Into<ImageSource>,
+        alt_text: &str,
+        options: ImageOptions,
+    ) -> Toast {
+        self.images.push(Image {
+            placement: ImagePlacement::Inline,
+            source: source.into(),
+            alt_text: alt_text.to_owned(),
+            options,
+        });
+        self
+    }
+
+    /// Set the image attributes inherited by every image of the toast.
+    ///
+    /// These are set on the `<visual>` element. [binding_image_defaults](Self::binding_image_defaults)
+    /// and the [options](Self::image_with) of an image override them.
+    pub fn image_defaults(mut self, defaults: ImageDefaults) -> Toast {
+        self.visual_images = defaults;
+        self
+    }
+
+    /// Set the image attributes of the `<binding>` element, which override the [image_defaults](Self::image_defaults).
+    pub fn binding_image_defaults(mut self, defaults: ImageDefaults) -> Toast {
+        self.binding_images = defaults;
         self
     }
 
@@ -477,23 +648,29 @@ impl Toast {
     ///
     /// Default is [Sound::IM](enum.Sound.html)
     pub fn sound(mut self, src: Option<Sound>) -> Toast {
-        self.audio = match src {
-            None => "<audio silent=\"true\" />".to_owned(),
-            Some(Sound::Default) => "".to_owned(),
-            Some(Sound::Loop(sound)) => format!(
-                r#"<audio loop="true" src="ms-winsoundevent:Notification.Looping.{}" />"#,
-                sound
-            ),
-            Some(Sound::Single(sound)) => format!(
-                r#"<audio src="ms-winsoundevent:Notification.Looping.{}" />"#,
-                sound
-            ),
-            Some(sound) => format!(r#"<audio src="ms-winsoundevent:Notification.{}" />"#, sound),
-        };
+        self.sound = src;
+        self
+    }
 
+    /// Set what happens to a [Sound::Loop] when the toast is not on screen long enough to loop it.
+    ///
+    /// Default is [LoopPolicy::Warn].
+    /// Alarms and incoming calls that keep the default sound loop the alarm or ringtone regardless.
+    pub fn loop_policy(mut self, policy: LoopPolicy) -> Toast {
+        self.loop_policy = policy;
         self
     }
 
+    /// The sound and duration the toast is shown with.
+    pub(crate) fn audio(&self) -> sound::Resolved {
+        sound::resolve(
+            self.sound.as_ref(),
+            self.duration,
+            self.scenario,
+            self.loop_policy,
+        )
+    }
+
     /// Adds a button to the notification
     /// `content` is the text of the button.
     /// `action` will be sent as an argument [on_activated](Self::on_activated) when the button is clicked.
@@ -501,40 +678,126 @@ impl Toast {
         self.buttons.push(Button {
             content: content.to_owned(),
             action: action.to_owned(),
+            style: None,
+            protocol: None,
         });
         self
     }
 
+    /// Adds a button opening `uri` with the app registered for its scheme, like a browser for links.
+    ///
+    /// The app showing the toast is not activated, so the click is not reported [on_activated](Self::on_activated).
+    pub fn add_protocol_button(mut self, content: &str, uri: &ProtocolUri) -> Toast {
+        self.buttons.push(Button {
+            content: content.to_owned(),
+            action: uri.as_str().to_owned(),
+            style: None,
+            protocol: Some(uri.target().map(str::to_owned)),
+        });
+        self
+    }
+
+    /// Adds a colored button to the notification, see [add_button](Self::add_button).
+    ///
+    /// Button styles require Windows 11, older versions show a regular button.
+    pub fn add_styled_button(mut self, content: &str, action: &str, style: ButtonStyle) -> Toast {
+        self.buttons.push(Button {
+            content: content.to_owned(),
+            action: action.to_owned(),
+            style: Some(style),
+            protocol: None,
+        });
+        self
+    }
+
+    /// Adds an input to the notification, shown above the buttons.
+    pub fn add_input(mut self, input: Input) -> Toast {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Set the template used on Windows 8.1, instead of the one fitting the number of texts and images.
+    ///
+    /// Has no effect on later versions of Windows.
+    pub fn legacy_template(mut self, template: LegacyTemplate) -> Toast {
+        self.legacy_template = Some(template);
+        self
+    }
+
+    /// Set the header the toast is grouped under in the action center.
+    pub fn header(mut self, header: &Header) -> Toast {
+        self.header = Some(header.clone());
+        self
+    }
+
     /// Set the progress for the toast
     pub fn progress(mut self, progress: &Progress) -> Toast {
         self.progress = Some(progress.clone());
         self
     }
 
+    /// Set a tag to uniquely identify the toast, so it can be hidden later with [Notifier::hide].
+    ///
+    /// If the toast has a [progress](Self::progress) bar, the tag of the progress is used instead.
+    pub fn tag(mut self, tag: &str) -> Toast {
+        self.tag = Some(tag.to_owned());
+        self
+    }
+
+    fn effective_tag(&self) -> Option<&str> {
+        self.progress
+            .as_ref()
+            .map(|progress| progress.tag.as_str())
+            .or(self.tag.as_deref())
+    }
+
+    /// Set the function to be called when the toast or one of its buttons is clicked
+    /// `f` will be called with the action of the clicked [button](Self::add_button), or the [launch](Self::launch) arguments
+    /// if the toast itself was clicked, `None` if it has none.
+    /// Use [on_activation](Self::on_activation) to tell the two apart.
+    ///
+    /// The handler is only called while the process is running, see [ToastHandle] for details.
     // HACK: f is static so that we know the function is valid to call.
     //       this would be nice to remove at some point
-    pub fn on_activated<F>(mut self, mut f: F) -> Self
+    pub fn on_activated<F>(mut self, f: F) -> Self
     where
         F: FnMut(Option<String>) -> Result<()> + Send + 'static,
     {
-        self.on_activated = Some(TypedEventHandler::new(move |_, insp| {
-            let _ = f(Self::get_activated_action(insp));
-            Ok(())
-        }));
+        self.on_activated = Some(Arc::new(Mutex::new(f)));
         self
     }
 
-    fn get_activated_action(insp: &Option<IInspectable>) -> Option<String> {
-        if let Some(insp) = insp {
-            if let Ok(args) = insp.cast::<ToastActivatedEventArgs>() {
-                if let Ok(arguments) = args.Arguments() {
-                    if !arguments.is_empty() {
-                        return Some(arguments.to_string());
-                    }
-                }
-            }
-        }
-        None
+    /// Set the function to be called with the whole [Activation] when the toast or one of its buttons is clicked,
+    /// including which part of the toast was clicked and the values of its inputs.
+    ///
+    /// Called after the [on_activated](Self::on_activated) handler if both are set.
+    /// The handler is only called while the process is running, see [ToastHandle] for details.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use tauri_winrt_notification::{ActivationTarget, Toast};
+    ///
+    /// Toast::new(Toast::POWERSHELL_APP_ID)
+    ///     .title("New message")
+    ///     .launch("open")
+    ///     .add_button("Archive", "archive")
+    ///     .on_activation(|activation| {
+    ///         match activation.target {
+    ///             ActivationTarget::Body => println!("opening the message"),
+    ///             ActivationTarget::Button => println!("clicked {:?}", activation.arguments),
+    ///             ActivationTarget::Header => println!("opening the conversation"),
+    ///         }
+    ///         Ok(())
+    ///     })
+    ///     .show()
+    ///     .expect("notification failed");
+    /// ```
+    pub fn on_activation<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(Activation) -> Result<()> + Send + 'static,
+    {
+        self.on_activation = Some(Arc::new(Mutex::new(f)));
+        self
     }
 
     /// Set the function to be called when the toast is dismissed
@@ -544,8 +807,10 @@ impl Toast {
     /// If the toast was dismissed because it timed out, the reason will be `ToastDismissalReason::TimedOut`.
     /// If the reason is unknown, the reason will be `None`.
     ///
+    /// The handler is only called while the process is running, see [ToastHandle] for details.
+    ///
     /// # Example
-    /// ```rust
+    /// ```rust,no_run
     /// use tauri_winrt_notification::{Toast, ToastDismissalReason};
     ///
     /// let toast = Toast::new(Toast::POWERSHELL_APP_ID);
@@ -563,83 +828,49 @@ impl Toast {
     where
         F: Fn(Option<ToastDismissalReason>) -> Result<()> + Send + 'static,
     {
-        self.on_dismissed = Some(TypedEventHandler::new(move |_, args| {
-            let _ = f(Self::get_dismissed_reason(args));
-            Ok(())
-        }));
+        self.on_dismissed = Some(Arc::new(Mutex::new(f)));
         self
     }
 
-    fn get_dismissed_reason(
-        args: &Option<ToastDismissedEventArgs>,
-    ) -> Option<ToastDismissalReason> {
-        if let Some(args) = args {
-            if let Ok(reason) = args.Reason() {
-                return Some(reason);
-            }
-        }
-        None
+    /// Set the function to be called when Windows fails to show the toast
+    /// `f` will be called with the error Windows reported, for example [Error::NotificationsDisabled].
+    ///
+    /// Failures at this point are not reported by [show](Self::show), which only fails if the toast
+    /// could not be handed to Windows at all.
+    /// The handler is only called while the process is running, see [ToastHandle] for details.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use tauri_winrt_notification::{Error, Toast};
+    ///
+    /// let toast = Toast::new(Toast::POWERSHELL_APP_ID);
+    /// toast.on_failed(|error| {
+    ///     match error {
+    ///         Error::NotificationsDisabled { .. } => println!("notifications are turned off"),
+    ///         error => println!("notification failed: {error}"),
+    ///     }
+    ///     Ok(())
+    /// }).show().expect("notification failed");
+    /// ```
+    pub fn on_failed<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(Error) -> Result<()> + Send + 'static,
+    {
+        self.on_failed = Some(Arc::new(Mutex::new(f)));
+        self
     }
 
-    fn create_template(&self) -> Result<ToastNotification> {
-        //using this to get an instance of XmlDocument
-        let toast_xml = XmlDocument::new()?;
-
-        let template_binding = if is_newer_than_windows81() {
-            "ToastGeneric"
-        } else {
-            // Need to do this or an empty placeholder will be shown if no image is set
-            if self.images.is_empty() {
-                "ToastText04"
-            } else {
-                "ToastImageAndText04"
-            }
-        };
-
-        let progress = match self.progress {
-            Some(_) => Progress::xml(),
-            None => "",
-        };
-
-        let mut actions = String::new();
-        if !self.buttons.is_empty() {
-            let _ = write!(actions, "<actions>");
-            for b in &self.buttons {
-                let _ = write!(
-                    actions,
-                    "<action content='{}' arguments='{}'/>",
-                    b.content, b.action
-                );
-            }
-            let _ = write!(actions, "</actions>");
-        }
-
-        toast_xml.LoadXml(&HSTRING::from(format!(
-            r#"<toast {} {}>
-                <visual>
-                    <binding template="{}">
-                        {}
-                        {}{}{}
-                        {}
-                    </binding>
-                </visual>
-                {}
-                {}
-            </toast>"#,
-            self.duration,
-            self.scenario,
-            template_binding,
-            self.images,
-            self.title,
-            self.line1,
-            self.line2,
-            progress,
-            self.audio,
-            actions
-        )))?;
+    /// Render the xml payload of the toast for `profile`.
+    ///
+    /// Parts of the toast `profile` does not support are downgraded or left out,
+    /// see [render](Self::render) to find out which.
+    pub fn to_xml(&self, profile: TargetProfile) -> String {
+        self.render(profile).xml
+    }
 
-        // Create the toast
-        ToastNotification::CreateToastNotification(&toast_xml).map_err(Into::into)
+    /// Render the toast for `profile` and report what had to be downgraded.
+    pub fn render(&self, profile: TargetProfile) -> Rendered {
+        render::render(self, profile)
     }
 
     /// Update progress bar title, status, progress value, progress value string
@@ -648,7 +879,7 @@ impl Toast {
     /// If no notification is found, the reason will be `NotificationUpdateResult::NotificationNotFound`.
     ///
     /// # Example
-    /// ```rust
+    /// ```rust,no_run
     /// use std::{thread::sleep, time::Duration as StdDuration};
     /// use tauri_winrt_notification::{Toast, Progress};
     ///
@@ -677,73 +908,106 @@ impl Toast {
     /// }
     /// ```
     pub fn set_progress(&self, progress: &Progress) -> Result<NotificationUpdateResult> {
-        let map = StringMap::new()?;
-        map.Insert(&HSTRING::from("progressTitle"), &progress.title())?;
-        map.Insert(&HSTRING::from("progressStatus"), &progress.status())?;
-        map.Insert(&HSTRING::from("progressValue"), &progress.value())?;
-        map.Insert(
-            &HSTRING::from("progressValueString"),
-            &progress.value_string(),
-        )?;
-
-        let data = NotificationData::CreateNotificationDataWithValuesAndSequenceNumber(&map, 2)?;
-
-        let toast_notifier =
-            ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(&self.app_id))?;
-
-        toast_notifier
-            .UpdateWithTag(&data, &progress.tag())
-            .map_err(Into::into)
+        self.notifier()?.update(progress)
     }
 
-    /// Display the toast on the screen
-    pub fn show(&self) -> Result<()> {
-        let toast_template = self.create_template()?;
-        if let Some(handler) = &self.on_activated {
-            toast_template.Activated(handler)?;
-        }
+    /// Check the toast against the limits Windows imposes on toasts.
+    ///
+    /// Windows silently drops toasts or content that break these rules, see [Violation] for the rules.
+    /// [Notifier::strict] runs this check whenever a toast is shown.
+    ///
+    /// The toast is rendered for the [detected](TargetProfile::detect) version of Windows, so the result
+    /// depends on the system it runs on. Use [validate_for](Self::validate_for) for results that do not.
+    pub fn validate(&self) -> Vec<Violation> {
+        self.validate_for(TargetProfile::detect())
+    }
 
-        if let Some(handler) = &self.on_dismissed {
-            toast_template.Dismissed(handler)?;
-        }
+    /// [Validate](Self::validate) the toast as rendered for `profile`.
+    pub fn validate_for(&self, profile: TargetProfile) -> Vec<Violation> {
+        validate::validate(self, profile)
+    }
 
-        let toast_notifier =
-            ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(&self.app_id))?;
+    /// A copy of the toast with every image replaced by `f`, which is called for all images in parallel.
+    fn map_images(&self, f: impl Fn(&Image) -> Result<Image> + Sync) -> Result<Toast> {
+        let images = std::thread::scope(|scope| {
+            let mapping: Vec<_> = self
+                .images
+                .iter()
+                .map(|image| scope.spawn(|| f(image)))
+                .collect();
+            mapping
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect::<Result<_>>()
+        })?;
+        Ok(Toast {
+            images,
+            ..self.clone()
+        })
+    }
 
-        if let Some(progress) = &self.progress {
-            toast_template.SetTag(&progress.tag())?;
+    /// Look for accessibility and UX problems that Windows does not reject, like images without alt text.
+    ///
+    /// Unlike [validate](Self::validate) this reads the images to check their size.
+    pub fn lint(&self) -> Vec<Lint> {
+        lint::lint(self)
+    }
 
-            let map = StringMap::new()?;
-            map.Insert(&HSTRING::from("progressTitle"), &progress.title())?;
-            map.Insert(&HSTRING::from("progressStatus"), &progress.status())?;
-            map.Insert(&HSTRING::from("progressValue"), &progress.value())?;
-            map.Insert(
-                &HSTRING::from("progressValueString"),
-                &progress.value_string(),
-            )?;
+    /// Stop [lint](Self::lint) from reporting `code` for this toast.
+    pub fn allow(mut self, code: LintCode) -> Toast {
+        self.allowed_lints.push(code);
+        self
+    }
 
-            let data =
-                NotificationData::CreateNotificationDataWithValuesAndSequenceNumber(&map, 1)?;
-            toast_template.SetData(&data)?;
-        }
+    /// Display the toast on the screen
+    ///
+    /// This creates a new [Notifier] every time, prefer [Notifier::show] when sending many toasts.
+    ///
+    /// Returns as soon as the toast is handed to Windows, which shows it asynchronously.
+    /// Earlier versions slept for 10ms here, a program that exits right after showing a toast
+    /// now has to call [ToastHandle::wait_delivered] first, otherwise the toast can get lost:
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration as StdDuration;
+    /// use tauri_winrt_notification::Toast;
+    ///
+    /// let handle = Toast::new(Toast::POWERSHELL_APP_ID)
+    ///     .title("Done")
+    ///     .show()
+    ///     .expect("notification failed");
+    /// handle
+    ///     .wait_delivered(StdDuration::from_secs(1))
+    ///     .expect("notification failed");
+    /// ```
+    ///
+    /// Keep the returned [ToastHandle] for as long as the [on_activated](Self::on_activated)
+    /// and [on_dismissed](Self::on_dismissed) handlers are needed.
+    pub fn show(&self) -> Result<ToastHandle> {
+        self.notifier()?.show(self)
+    }
 
-        // Show the toast.
-        let result = toast_notifier.Show(&toast_template).map_err(Into::into);
-        std::thread::sleep(std::time::Duration::from_millis(10));
-        result
+    /// Display the toast and block until the user reacts to it or `timeout` elapses.
+    ///
+    /// See [Notifier::show_and_wait].
+    pub fn show_and_wait(&self, timeout: StdDuration) -> Result<ToastOutcome> {
+        self.notifier()?.show_and_wait(self, timeout)
     }
-}
 
-fn is_newer_than_windows81() -> bool {
-    let os = windows_version::OsVersion::current();
-    os.major > 6
+    /// A notifier for the app id of the toast.
+    pub(crate) fn notifier(&self) -> Result<Notifier> {
+        Notifier::new(self.app_id.as_deref().ok_or(Error::MissingAppId)?)
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, windows))]
 mod tests {
+    use std::path::Path;
+
     use super::*;
 
     #[test]
+    // borrowed like in the README
+    #[allow(clippy::needless_borrows_for_generic_args)]
     fn simple_toast() {
         let toast = Toast::new(Toast::POWERSHELL_APP_ID);
         toast
@@ -0,0 +1,296 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Accessibility and UX checks that go beyond the rules Windows enforces.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{dimensions, IconCrop, Image, ImagePlacement, Toast};
+
+/// Titles longer than this are usually cut off.
+pub(crate) const MAX_TITLE_LEN: usize = 60;
+
+/// Identifies a kind of [Lint], to [allow](Toast::allow) it.
+///
+/// The codes returned by [as_str](Self::as_str) are stable and can be kept in configuration files.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintCode {
+    /// An image without alt text, so screen readers cannot describe it.
+    MissingAltText,
+    /// A title longer than 60 characters, which is cut off on screen.
+    LongTitle,
+    /// A circular icon or [circle cropped](crate::ImageOptions::circle_crop) image that is not square,
+    /// which cuts off part of the image.
+    NonSquareCircularCrop,
+    /// A hero image without a title, which looks like an ad.
+    HeroWithoutTitle,
+}
+
+impl LintCode {
+    /// Every lint code, in the order [Toast::lint] reports them.
+    pub const ALL: [LintCode; 4] = [
+        LintCode::MissingAltText,
+        LintCode::LongTitle,
+        LintCode::NonSquareCircularCrop,
+        LintCode::HeroWithoutTitle,
+    ];
+
+    /// The stable textual form of the code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintCode::MissingAltText => "missing-alt-text",
+            LintCode::LongTitle => "long-title",
+            LintCode::NonSquareCircularCrop => "non-square-circular-crop",
+            LintCode::HeroWithoutTitle => "hero-without-title",
+        }
+    }
+}
+
+impl Display for LintCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A string is not a [LintCode].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintCodeParsingError {
+    input: String,
+}
+
+impl LintCodeParsingError {
+    /// The string that could not be parsed.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl Display for LintCodeParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let codes = LintCode::ALL.map(|code| code.as_str()).join(", ");
+        write!(
+            f,
+            "`{}` is not a lint code, expected one of {codes}",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for LintCodeParsingError {}
+
+impl FromStr for LintCode {
+    type Err = LintCodeParsingError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        LintCode::ALL
+            .into_iter()
+            .find(|code| code.as_str() == s)
+            .ok_or_else(|| LintCodeParsingError {
+                input: s.to_owned(),
+            })
+    }
+}
+
+/// A warning about the content of a toast, found by [Toast::lint].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// What kind of problem this is.
+    pub code: LintCode,
+    /// A description of the problem, meant for humans.
+    pub message: String,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+pub(crate) fn lint(toast: &Toast) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut push = |code, message: String| {
+        if !toast.allowed_lints.contains(&code) {
+            lints.push(Lint { code, message });
+        }
+    };
+
+    for image in &toast.images {
+        if image.alt_text.trim().is_empty() {
+            push(
+                LintCode::MissingAltText,
+                format!("{} has no alt text", image.source),
+            );
+        }
+    }
+
+    if let Some(title) = &toast.title {
+        let length = title.chars().count();
+        if length > MAX_TITLE_LEN {
+            push(
+                LintCode::LongTitle,
+                format!("the title is {length} characters long, only about {MAX_TITLE_LEN} fit"),
+            );
+        }
+    }
+
+    for image in toast.images.iter().filter(|image| circle_cropped(image)) {
+        let size = match image.source.as_bytes() {
+            Some(data) => dimensions::parse(data),
+            None => image.source.as_path().and_then(dimensions::read),
+        };
+        if let Some((width, height)) = size {
+            if width != height {
+                push(
+                    LintCode::NonSquareCircularCrop,
+                    format!(
+                        "{} is {width}x{height}, a circular crop cuts off its sides",
+                        image.source
+                    ),
+                );
+            }
+        }
+    }
+
+    let has_hero = toast
+        .images
+        .iter()
+        .any(|image| matches!(image.placement, ImagePlacement::Hero));
+    if has_hero
+        && toast
+            .title
+            .as_deref()
+            .map_or(true, |title| title.trim().is_empty())
+    {
+        push(
+            LintCode::HeroWithoutTitle,
+            "the toast has a hero image but no title".to_owned(),
+        );
+    }
+
+    lints
+}
+
+/// Whether `image` is shown cropped to a circle, as a circular icon or a [circle cropped](crate::ImageOptions::circle_crop) inline image.
+fn circle_cropped(image: &Image) -> bool {
+    match image.placement {
+        ImagePlacement::AppLogo(crop) => crop == IconCrop::Circular,
+        ImagePlacement::Inline => image.options.circle_crop,
+        ImagePlacement::Hero => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn resource(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test")
+            .join(name)
+    }
+
+    fn codes(toast: &Toast) -> Vec<LintCode> {
+        toast.lint().into_iter().map(|lint| lint.code).collect()
+    }
+
+    #[test]
+    fn clean_toast() {
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID)
+            .title("Flowers")
+            .hero(resource("flower.jpeg"), "a flower")
+            .icon(resource("chick.jpeg"), IconCrop::Square, "a chick");
+        assert_eq!(toast.lint(), []);
+    }
+
+    #[test]
+    fn finds_problems() {
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID)
+            .hero(resource("flower.jpeg"), " ")
+            .icon(resource("chick.jpeg"), IconCrop::Circular, "a chick");
+        assert_eq!(
+            codes(&toast),
+            [
+                LintCode::MissingAltText,
+                LintCode::NonSquareCircularCrop,
+                LintCode::HeroWithoutTitle
+            ]
+        );
+
+        let toast = toast.title(&"a".repeat(MAX_TITLE_LEN + 1));
+        assert_eq!(
+            codes(&toast),
+            [
+                LintCode::MissingAltText,
+                LintCode::LongTitle,
+                LintCode::NonSquareCircularCrop
+            ]
+        );
+    }
+
+    #[test]
+    fn in_memory_images() {
+        let data = std::fs::read(resource("chick.jpeg")).unwrap();
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).icon(
+            crate::ImageSource::bytes(&data),
+            IconCrop::Circular,
+            "a chick",
+        );
+        assert_eq!(codes(&toast), [LintCode::NonSquareCircularCrop]);
+    }
+
+    #[test]
+    fn circle_cropped_inline_images() {
+        let options = crate::ImageOptions {
+            circle_crop: true,
+            ..Default::default()
+        };
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).image_with(
+            resource("chick.jpeg"),
+            "a chick",
+            options,
+        );
+        assert_eq!(codes(&toast), [LintCode::NonSquareCircularCrop]);
+
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).image(resource("chick.jpeg"), "a chick");
+        assert_eq!(codes(&toast), []);
+    }
+
+    #[test]
+    fn allowed_lints_are_suppressed() {
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID)
+            .hero(resource("flower.jpeg"), "")
+            .allow(LintCode::MissingAltText);
+        assert_eq!(codes(&toast), [LintCode::HeroWithoutTitle]);
+        assert_eq!(codes(&toast.allow(LintCode::HeroWithoutTitle)), []);
+    }
+
+    #[test]
+    fn unreadable_images_are_skipped() {
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).icon(
+            Path::new("missing.png"),
+            IconCrop::Circular,
+            "missing",
+        );
+        assert_eq!(toast.lint(), []);
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for code in LintCode::ALL {
+            assert_eq!(code.as_str().parse::<LintCode>().unwrap(), code);
+        }
+        let err = "unknown".parse::<LintCode>().unwrap_err();
+        assert_eq!(err.input(), "unknown");
+        assert_eq!(
+            err.to_string(),
+            "`unknown` is not a lint code, expected one of missing-alt-text, long-title, \
+             non-square-circular-crop, hero-without-title"
+        );
+    }
+}
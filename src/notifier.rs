@@ -0,0 +1,1048 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration as StdDuration, Instant};
+
+use crate::backend::{self, Backend, EventHandler, ShowRequest, ShownToast};
+use crate::event::Targets;
+#[cfg(feature = "image-processing")]
+use crate::ImageProcessor;
+#[cfg(feature = "remote-images")]
+use crate::RemoteImages;
+use crate::{validate, xml};
+use crate::{
+    Capability, Downgrade, Error, Image, ImageCache, ImageSource, LoopPolicy,
+    NotificationUpdateResult, Progress, Rendered, Result, TargetProfile, Toast, ToastEvent,
+    ToastOutcome, Violation,
+};
+
+/// How often [ToastHandle::wait_delivered] looks at the action center.
+const DELIVERY_POLL_INTERVAL: StdDuration = StdDuration::from_millis(50);
+/// How long [ToastHandle::wait_delivered] waits without an action center, as `show` did before it returned a handle.
+const DELIVERY_GRACE_PERIOD: StdDuration = StdDuration::from_millis(10);
+
+/// Receives the events of a single shown toast.
+pub(crate) type Listener = Box<dyn FnMut(ToastEvent) + Send>;
+
+/// Receives the tag and events of every toast shown by a notifier,
+/// returns `false` once it is no longer interested.
+pub(crate) type Subscriber = Box<dyn FnMut(&str, &ToastEvent) -> bool + Send>;
+
+/// A long-lived sender of toasts for a single [AppUserModelID][1].
+///
+/// Creating the WinRT notifier is not free, so applications that send many toasts
+/// should create a `Notifier` once and reuse it instead of calling [Toast::show].
+/// A toast sent through a `Notifier` is shown under the notifier's app id,
+/// the app id the [Toast] was created with is ignored, so toasts can be created with [Toast::default].
+///
+/// [1]: https://msdn.microsoft.com/en-us/library/windows/desktop/dd378459(v=vs.85).aspx
+///
+/// # Example
+/// ```rust,no_run
+/// use tauri_winrt_notification::{Notifier, Toast};
+///
+/// let notifier = Notifier::new(Toast::POWERSHELL_APP_ID).expect("notifier unavailable");
+///
+/// for i in 1..=3 {
+///     let toast = Toast::default().title(&format!("toast #{i}"));
+///     notifier.show(&toast).expect("notification failed");
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Notifier {
+    app_id: String,
+    backend: Arc<dyn Backend>,
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    strict: bool,
+    profile: TargetProfile,
+    image_cache: ImageCache,
+    #[cfg(feature = "remote-images")]
+    remote_images: Option<RemoteImages>,
+    #[cfg(feature = "image-processing")]
+    image_processor: Option<ImageProcessor>,
+}
+
+impl Notifier {
+    /// Create a notifier for `app_id`.
+    ///
+    /// If the program you are using this in was not installed, use [Toast::POWERSHELL_APP_ID] for now
+    ///
+    /// Fails with [Error::BackendUnavailable] on platforms other than Windows.
+    pub fn new(app_id: &str) -> Result<Notifier> {
+        Ok(Notifier::with_backend(app_id, backend::platform(app_id)?))
+    }
+
+    pub(crate) fn with_backend(app_id: &str, backend: Arc<dyn Backend>) -> Notifier {
+        Notifier {
+            app_id: app_id.to_owned(),
+            backend,
+            subscribers: Default::default(),
+            strict: false,
+            profile: TargetProfile::detect(),
+            image_cache: ImageCache::default(),
+            #[cfg(feature = "remote-images")]
+            remote_images: None,
+            #[cfg(feature = "image-processing")]
+            image_processor: None,
+        }
+    }
+
+    /// Refuse to show toasts that break any rule checked by [Toast::validate], warnings included.
+    ///
+    /// Such toasts fail with [Error::Violations] instead of being shown,
+    /// which is meant to catch mistakes in tests and debug builds.
+    pub fn strict(mut self, strict: bool) -> Notifier {
+        self.strict = strict;
+        self
+    }
+
+    /// Render toasts for `profile` instead of the [detected](TargetProfile::detect) version of Windows.
+    pub fn with_profile(mut self, profile: TargetProfile) -> Notifier {
+        self.profile = profile;
+        self
+    }
+
+    /// The profile toasts are rendered for.
+    pub fn profile(&self) -> TargetProfile {
+        self.profile
+    }
+
+    /// Write [in-memory images](crate::ImageSource::bytes) to `cache` instead of the [default](ImageCache::default) one.
+    pub fn with_image_cache(mut self, cache: ImageCache) -> Notifier {
+        self.image_cache = cache;
+        self
+    }
+
+    /// Download `http(s)` images into the [image cache](Self::with_image_cache) before showing toasts.
+    ///
+    /// Windows only loads remote images for packaged apps.
+    #[cfg(feature = "remote-images")]
+    pub fn with_remote_images(mut self, remote_images: RemoteImages) -> Notifier {
+        self.remote_images = Some(remote_images);
+        self
+    }
+
+    /// Resize, crop and convert images with `image_processor` before showing toasts.
+    ///
+    /// The processed images are written to the [image cache](Self::with_image_cache).
+    #[cfg(feature = "image-processing")]
+    pub fn with_image_processor(mut self, image_processor: ImageProcessor) -> Notifier {
+        self.image_processor = Some(image_processor);
+        self
+    }
+
+    /// The AppUserModelID this notifier sends toasts for.
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    /// Display the toast on the screen
+    ///
+    /// Windows shows the toast asynchronously, so it may not be on the screen yet when this returns.
+    /// A program that exits right after showing a toast should call [ToastHandle::wait_delivered]
+    /// first, otherwise the toast can get lost.
+    ///
+    /// Toasts without a [tag](Toast::tag) are given a unique one, see [ToastHandle::tag].
+    pub fn show(&self, toast: &Toast) -> Result<ToastHandle> {
+        self.show_with_listener(toast, None)
+    }
+
+    /// Display a hand-written toast payload, like one designed with the Notifications Visualizer.
+    ///
+    /// This is the only way to hand raw xml to Windows, everything a [Toast] renders is escaped.
+    /// The payload is checked for well-formedness first and fails with [Error::InvalidXml],
+    /// pointing at the problem, instead of the opaque error of Windows. It is not [validated](Toast::validate).
+    ///
+    /// The toast is given a unique [tag](ToastHandle::tag). It has no handlers,
+    /// its events only reach the event streams of the notifier of the `async` feature.
+    pub fn show_xml(&self, xml: &str) -> Result<ToastHandle> {
+        xml::check(xml)?;
+
+        let tag = unique_tag();
+        let (listener, failed) = watch_failure(None);
+        let shown = self.backend.show(ShowRequest {
+            xml,
+            tag: &tag,
+            progress: None,
+            handler: self.event_handler(&Toast::default(), &tag, Some(listener)),
+        })?;
+
+        Ok(ToastHandle {
+            notifier: self.clone(),
+            shown,
+            tag,
+            downgrades: Vec::new(),
+            failed,
+        })
+    }
+
+    /// Display the toast and block until the user reacts to it or `timeout` elapses.
+    ///
+    /// The [on_activated](Toast::on_activated), [on_dismissed](Toast::on_dismissed) and
+    /// [on_failed](Toast::on_failed) handlers of the toast are still called. A toast that timed out is left on the screen.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::time::Duration as StdDuration;
+    /// use tauri_winrt_notification::{Notifier, Toast, ToastOutcome};
+    ///
+    /// let notifier = Notifier::new(Toast::POWERSHELL_APP_ID).expect("notifier unavailable");
+    /// let toast = Toast::new(Toast::POWERSHELL_APP_ID)
+    ///     .title("Install update?")
+    ///     .add_button("Install", "install")
+    ///     .add_button("Later", "later");
+    ///
+    /// match notifier.show_and_wait(&toast, StdDuration::from_secs(30)) {
+    ///     Ok(ToastOutcome::Activated(activation)) => println!("{:?}", activation.arguments),
+    ///     Ok(outcome) => println!("no answer: {outcome:?}"),
+    ///     Err(err) => eprintln!("notification failed: {err}"),
+    /// }
+    /// ```
+    pub fn show_and_wait(&self, toast: &Toast, timeout: StdDuration) -> Result<ToastOutcome> {
+        let (sender, receiver) = mpsc::channel();
+        // the handle keeps the handlers alive while waiting
+        let _handle = self.show_with_listener(
+            toast,
+            Some(Box::new(move |event| {
+                let _ = sender.send(event);
+            })),
+        )?;
+
+        Ok(match receiver.recv_timeout(timeout) {
+            Ok(event) => event.into(),
+            Err(_) => ToastOutcome::TimedOut,
+        })
+    }
+
+    /// Whether `source` has to be downloaded, processed or written to the image cache before Windows can show it.
+    fn needs_preparing(&self, source: &ImageSource) -> bool {
+        #[cfg(feature = "remote-images")]
+        if self.remote_images.is_some() && source.as_remote_uri().is_some() {
+            return true;
+        }
+        #[cfg(feature = "image-processing")]
+        if self.image_processor.is_some() && source.as_path().is_some() {
+            return true;
+        }
+        source.as_bytes().is_some()
+    }
+
+    fn prepare_image(&self, image: &Image, cache: &ImageCache) -> Result<Image> {
+        let mut image = image.clone();
+        #[cfg(feature = "remote-images")]
+        if let Some(remote_images) = &self.remote_images {
+            image.source = remote_images.resolve(&image.source, cache);
+        }
+        #[cfg(feature = "image-processing")]
+        if let Some(image_processor) = &self.image_processor {
+            image = image_processor.process(&image, cache);
+        }
+        image.source = image.source.store(cache)?;
+        Ok(image)
+    }
+
+    pub(crate) fn show_with_listener(
+        &self,
+        toast: &Toast,
+        listener: Option<Listener>,
+    ) -> Result<ToastHandle> {
+        // checked before the images are prepared, so a refused toast downloads and stores nothing
+        if toast.loop_policy == LoopPolicy::Reject && toast.audio().loop_too_short {
+            return Err(Error::Violations(vec![Violation::LoopNeedsLongDuration {
+                policy: LoopPolicy::Reject,
+            }]));
+        }
+        if self.strict {
+            let violations = toast.validate_for(self.profile);
+            if !violations.is_empty() {
+                return Err(Error::Violations(violations));
+            }
+        }
+
+        let prepared;
+        let toast = if toast
+            .images
+            .iter()
+            .any(|image| self.needs_preparing(&image.source))
+        {
+            // clean once every image is stored, so storing one image cannot remove another one of the toast,
+            // and download and process them in parallel, so the toast waits for its slowest image only
+            let cache = self.image_cache.without_cleaning();
+            prepared = toast.map_images(|image| self.prepare_image(image, &cache))?;
+            let paths: Vec<&Path> = prepared
+                .images
+                .iter()
+                .filter_map(|image| image.source.as_path())
+                .collect();
+            self.image_cache.clean_keeping(&paths);
+            &prepared
+        } else {
+            toast
+        };
+
+        let tag = match toast.effective_tag() {
+            Some(tag) => tag.to_owned(),
+            None => unique_tag(),
+        };
+
+        let Rendered { xml, downgrades } = toast.render(self.profile);
+        xml::check(&xml)?;
+        // the prepared images change the size of the payload
+        if self.strict {
+            if let Some(violation) = validate::payload_size(&xml) {
+                return Err(Error::Violations(vec![violation]));
+            }
+        }
+
+        let (listener, failed) = watch_failure(listener);
+        let shown = self.backend.show(ShowRequest {
+            xml: &xml,
+            tag: &tag,
+            progress: toast.progress.as_ref(),
+            handler: self.event_handler(toast, &tag, Some(listener)),
+        })?;
+
+        Ok(ToastHandle {
+            notifier: self.clone(),
+            shown,
+            tag,
+            downgrades,
+            failed,
+        })
+    }
+
+    /// Register `subscriber` for the events of every toast shown from now on.
+    #[cfg(feature = "async")]
+    pub(crate) fn subscribe(&self, subscriber: Subscriber) {
+        self.subscribers.lock().unwrap().push(subscriber);
+    }
+
+    /// Forward the events of a shown toast to the handlers of `toast`, to `listener` and to the subscribers.
+    fn event_handler(&self, toast: &Toast, tag: &str, listener: Option<Listener>) -> EventHandler {
+        let on_activated = toast.on_activated.clone();
+        let on_activation = toast.on_activation.clone();
+        let on_dismissed = toast.on_dismissed.clone();
+        let on_failed = toast.on_failed.clone();
+        let targets = Targets::of(toast);
+        let listener = listener.map(Mutex::new);
+        let subscribers = self.subscribers.clone();
+        let tag = tag.to_owned();
+
+        Arc::new(move |mut event: ToastEvent| {
+            if let ToastEvent::Activated(activation) = &mut event {
+                targets.classify(activation);
+            }
+            match &event {
+                ToastEvent::Activated(activation) => {
+                    if let Some(f) = &on_activated {
+                        let _ = f.lock().unwrap()(activation.arguments.clone());
+                    }
+                    if let Some(f) = &on_activation {
+                        let _ = f.lock().unwrap()(activation.clone());
+                    }
+                }
+                ToastEvent::Dismissed(reason) => {
+                    if let Some(f) = &on_dismissed {
+                        let _ = f.lock().unwrap()(*reason);
+                    }
+                }
+                ToastEvent::Failed(failure) => {
+                    if let Some(f) = &on_failed {
+                        let _ = f.lock().unwrap()((*failure).into());
+                    }
+                }
+            }
+
+            subscribers
+                .lock()
+                .unwrap()
+                .retain_mut(|subscriber| subscriber(&tag, &event));
+
+            if let Some(listener) = &listener {
+                listener.lock().unwrap()(event);
+            }
+        })
+    }
+
+    /// Update the progress bar of a toast previously shown with the same [Progress] tag.
+    ///
+    /// See [Toast::set_progress] for the meaning of the returned value.
+    pub fn update(&self, progress: &Progress) -> Result<NotificationUpdateResult> {
+        self.backend.update(progress)
+    }
+
+    /// Remove a toast previously shown by this notifier from the screen and the action center.
+    ///
+    /// Toasts are found by their tag, see [Toast::tag].
+    /// Does nothing if the toast has no tag.
+    pub fn hide(&self, toast: &Toast) -> Result<()> {
+        match toast.effective_tag() {
+            Some(tag) => self.history()?.remove(tag),
+            None => Ok(()),
+        }
+    }
+
+    /// Access the toasts of this app that are currently in the action center.
+    pub fn history(&self) -> Result<History> {
+        Ok(History {
+            backend: self.backend.clone(),
+        })
+    }
+}
+
+/// A toast that was handed to Windows by [Notifier::show].
+///
+/// The handle keeps the WinRT notification, and with it the [on_activated](Toast::on_activated)
+/// and [on_dismissed](Toast::on_dismissed) handlers, alive. Dropping it does not remove the toast
+/// or unregister the handlers, but from then on it is up to Windows how long the notification
+/// object lives, so keep the handle around for as long as you rely on the handlers.
+/// No handler is called once the process has exited, activating the toast then launches
+/// the app registered for the AppUserModelID instead.
+pub struct ToastHandle {
+    notifier: Notifier,
+    shown: Box<dyn ShownToast>,
+    tag: String,
+    downgrades: Vec<Downgrade>,
+    failed: Arc<AtomicBool>,
+}
+
+impl ToastHandle {
+    /// The tag the toast was shown with.
+    ///
+    /// This is the [Toast::tag] or [Progress] tag if one was set, or a generated unique tag otherwise.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// What had to be changed or left out because the notifier's [profile](Notifier::profile) does not support it.
+    pub fn downgrades(&self) -> &[Downgrade] {
+        &self.downgrades
+    }
+
+    /// Block until the toast shows up in the action center or `timeout` elapses.
+    ///
+    /// Returns `false` on timeout, which also happens when Windows drops the toast,
+    /// for example because notifications are turned off for the app,
+    /// and right away once Windows reports that it [failed](ToastEvent::Failed) to show the toast.
+    ///
+    /// Windows 8.1 has no action center, so for a notifier with that [profile](Notifier::profile)
+    /// this only waits 10ms, like `show` did before it returned a handle, and returns `true`.
+    pub fn wait_delivered(&self, timeout: StdDuration) -> Result<bool> {
+        if !self.notifier.profile.supports(Capability::History) {
+            std::thread::sleep(DELIVERY_GRACE_PERIOD.min(timeout));
+            return Ok(true);
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.failed.load(Ordering::SeqCst) {
+                return Ok(false);
+            }
+            if self.is_delivered()? {
+                return Ok(true);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(false);
+            }
+            std::thread::sleep(DELIVERY_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    fn is_delivered(&self) -> Result<bool> {
+        let entries = self.notifier.history()?.entries()?;
+        Ok(entries.iter().any(|entry| entry.tag == self.tag))
+    }
+
+    /// Remove the toast from the screen.
+    pub fn hide(&self) -> Result<()> {
+        self.shown.hide()
+    }
+}
+
+/// Wrap `listener` to also record whether the toast failed,
+/// which lets its handle stop waiting for a toast that will never be delivered.
+fn watch_failure(mut listener: Option<Listener>) -> (Listener, Arc<AtomicBool>) {
+    let failed = Arc::new(AtomicBool::new(false));
+    let watching = failed.clone();
+    let listener = Box::new(move |event: ToastEvent| {
+        if let ToastEvent::Failed(_) = event {
+            watching.store(true, Ordering::SeqCst);
+        }
+        if let Some(listener) = &mut listener {
+            listener(event);
+        }
+    });
+    (listener, failed)
+}
+
+/// Generate a tag that is unique for the lifetime of the process.
+///
+/// Windows 8.1 limits tags to 16 characters.
+fn unique_tag() -> String {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    format!(
+        "{:08x}{:08x}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// The toasts of an app that are still in the action center.
+///
+/// Obtained from [Notifier::history].
+pub struct History {
+    backend: Arc<dyn Backend>,
+}
+
+/// A toast found in the action center.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The tag the toast was shown with, empty if it had none.
+    pub tag: String,
+    /// The group the toast was shown with, empty if it had none.
+    pub group: String,
+}
+
+impl History {
+    /// List the toasts currently in the action center.
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        self.backend.history()
+    }
+
+    /// Remove the toast with `tag` that was shown without a group.
+    pub fn remove(&self, tag: &str) -> Result<()> {
+        self.backend.remove(tag, None)
+    }
+
+    /// Remove the toast with `tag` in `group`.
+    pub fn remove_grouped(&self, tag: &str, group: &str) -> Result<()> {
+        self.backend.remove(tag, Some(group))
+    }
+
+    /// Remove every toast in `group`.
+    pub fn remove_group(&self, group: &str) -> Result<()> {
+        self.backend.remove_group(group)
+    }
+
+    /// Remove every toast of this app.
+    pub fn clear(&self) -> Result<()> {
+        self.backend.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::simulated::SimulatedBackend;
+    use crate::{Activation, ActivationTarget, Header, ToastDismissalReason, ToastFailure};
+    use std::collections::HashMap;
+
+    fn notifier() -> (Notifier, SimulatedBackend) {
+        let backend = SimulatedBackend::default();
+        let notifier = Notifier::with_backend(Toast::POWERSHELL_APP_ID, Arc::new(backend.clone()));
+        (notifier, backend)
+    }
+
+    fn toast() -> Toast {
+        Toast::new(Toast::POWERSHELL_APP_ID)
+            .title("prompt")
+            .add_button("Yes", "yes")
+            .add_button("No", "no")
+    }
+
+    #[test]
+    fn show_and_wait_activated() {
+        let (notifier, backend) = notifier();
+        let activation = Activation {
+            arguments: Some("yes".to_owned()),
+            target: ActivationTarget::Button,
+            user_input: HashMap::from([("reply".to_owned(), "hello".to_owned())]),
+        };
+        backend.react_with(ToastEvent::Activated(activation.clone()));
+
+        let clicked = Arc::new(Mutex::new(None));
+        let toast = toast().on_activated({
+            let clicked = clicked.clone();
+            move |action| {
+                *clicked.lock().unwrap() = action;
+                Ok(())
+            }
+        });
+
+        let outcome = notifier
+            .show_and_wait(&toast, StdDuration::from_secs(5))
+            .unwrap();
+        assert_eq!(outcome, ToastOutcome::Activated(activation));
+        assert_eq!(clicked.lock().unwrap().as_deref(), Some("yes"));
+    }
+
+    #[test]
+    fn show_and_wait_dismissed() {
+        let (notifier, backend) = notifier();
+        backend.react_with(ToastEvent::Dismissed(Some(
+            ToastDismissalReason::UserCanceled,
+        )));
+
+        let outcome = notifier
+            .show_and_wait(&toast(), StdDuration::from_secs(5))
+            .unwrap();
+        assert_eq!(
+            outcome,
+            ToastOutcome::Dismissed(Some(ToastDismissalReason::UserCanceled))
+        );
+    }
+
+    #[test]
+    fn show_and_wait_failed() {
+        let (notifier, backend) = notifier();
+        let failure = ToastFailure::new(0x803E0111_u32 as i32);
+        backend.react_with(ToastEvent::Failed(failure));
+
+        let error = Arc::new(Mutex::new(None));
+        let toast = toast().on_failed({
+            let error = error.clone();
+            move |e| {
+                *error.lock().unwrap() = Some(e);
+                Ok(())
+            }
+        });
+
+        let outcome = notifier
+            .show_and_wait(&toast, StdDuration::from_secs(5))
+            .unwrap();
+        assert_eq!(outcome, ToastOutcome::Failed(failure));
+        assert!(matches!(
+            *error.lock().unwrap(),
+            Some(Error::NotificationsDisabled { .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_failures_keep_their_code() {
+        let failure = ToastFailure::new(0x80004005_u32 as i32);
+        assert!(matches!(failure.error(), Error::ToastFailed(code) if code == failure.code()));
+        assert_eq!(
+            failure.to_string(),
+            "Windows failed to show the toast (HRESULT 0x80004005)"
+        );
+    }
+
+    #[test]
+    fn show_and_wait_timed_out() {
+        let (notifier, _backend) = notifier();
+
+        let outcome = notifier
+            .show_and_wait(&toast(), StdDuration::from_millis(10))
+            .unwrap();
+        assert_eq!(outcome, ToastOutcome::TimedOut);
+    }
+
+    #[test]
+    fn show_and_wait_event_from_other_thread() {
+        let (notifier, backend) = notifier();
+        let toast = toast().tag("prompt");
+
+        let raiser = std::thread::spawn(move || {
+            while backend.shown().is_empty() {
+                std::thread::sleep(StdDuration::from_millis(1));
+            }
+            backend.raise(
+                "prompt",
+                ToastEvent::Activated(Activation {
+                    arguments: Some("no".to_owned()),
+                    ..Default::default()
+                }),
+            );
+        });
+
+        let outcome = notifier
+            .show_and_wait(&toast, StdDuration::from_secs(5))
+            .unwrap();
+        raiser.join().unwrap();
+        match outcome {
+            ToastOutcome::Activated(activation) => {
+                assert_eq!(activation.arguments.as_deref(), Some("no"))
+            }
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+
+    #[test]
+    fn untagged_toasts_get_unique_tags() {
+        let (notifier, backend) = notifier();
+        let toast = toast();
+
+        let first = notifier.show(&toast).unwrap();
+        let second = notifier.show(&toast).unwrap();
+        assert_ne!(first.tag(), second.tag());
+        assert!(first.tag().len() <= 16);
+        assert_eq!(backend.shown().len(), 2);
+        assert!(backend.shown()[0]
+            .xml
+            .contains(r#"<text id="1">prompt</text>"#));
+
+        assert!(first.wait_delivered(StdDuration::from_secs(1)).unwrap());
+    }
+
+    #[test]
+    fn toasts_without_app_id_need_a_notifier() {
+        let (notifier, backend) = notifier();
+        let toast = Toast::default().title("content");
+
+        assert!(matches!(toast.show(), Err(Error::MissingAppId)));
+        let progress = Progress {
+            tag: "tag".to_owned(),
+            title: "title".to_owned(),
+            status: "status".to_owned(),
+            value: 0.0,
+            value_string: "0%".to_owned(),
+        };
+        assert!(matches!(
+            toast.set_progress(&progress),
+            Err(Error::MissingAppId)
+        ));
+        notifier.show(&toast).unwrap();
+        assert_eq!(backend.shown().len(), 1);
+    }
+
+    #[test]
+    fn malformed_xml_is_not_shown() {
+        let (notifier, backend) = notifier();
+        let xml = concat!(
+            "<toast><visual><binding template=\"ToastGeneric\">\n",
+            "<text id=\"1\">Don't & go</text>\n",
+            "</binding></visual></toast>"
+        );
+
+        match notifier.show_xml(xml) {
+            Err(Error::InvalidXml { line, snippet, .. }) => {
+                assert_eq!(line, 2);
+                assert!(snippet.contains("Don't"), "{snippet}")
+            }
+            result => panic!("expected invalid xml, got {:?}", result.map(|_| ())),
+        }
+        assert!(backend.shown().is_empty());
+
+        let handle = notifier.show_xml(&xml.replace('&', "&amp;")).unwrap();
+        assert_eq!(backend.shown().len(), 1);
+        assert_eq!(backend.shown()[0].tag, handle.tag());
+    }
+
+    #[test]
+    fn strict_rejects_violations() {
+        let (notifier, backend) = notifier();
+        let notifier = notifier.strict(true);
+        let toast = toast()
+            .scenario(crate::Scenario::IncomingCall)
+            .sound(Some(crate::Sound::Loop(crate::LoopableSound::Call)));
+        notifier.show(&toast).unwrap();
+
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).scenario(crate::Scenario::IncomingCall);
+        match notifier.show(&toast) {
+            Err(Error::Violations(violations)) => {
+                assert_eq!(violations, [crate::Violation::MissingCallActions])
+            }
+            result => panic!("expected violations, got {:?}", result.map(|_| ())),
+        }
+        assert_eq!(backend.shown().len(), 1);
+
+        // not strict by default
+        notifier.strict(false).show(&toast).unwrap();
+    }
+
+    #[test]
+    fn refused_toasts_prepare_no_images() {
+        let dir = std::env::temp_dir()
+            .join("tauri-winrt-notification-tests")
+            .join(format!("notifier-refused-{}", std::process::id()));
+        let (notifier, _backend) = notifier();
+        let notifier = notifier.with_image_cache(ImageCache::new(&dir));
+        let toast = toast().image(ImageSource::bytes(b"\x89PNG\r\n\x1a\nimage"), "image");
+
+        let looping = toast
+            .clone()
+            .sound(Some(crate::Sound::Loop(crate::LoopableSound::Alarm)))
+            .loop_policy(LoopPolicy::Reject);
+        assert!(matches!(notifier.show(&looping), Err(Error::Violations(_))));
+        // the toast itself sends the arguments of its button
+        let ambiguous = toast.launch("yes");
+        assert!(matches!(
+            notifier.clone().strict(true).show(&ambiguous),
+            Err(Error::Violations(_))
+        ));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn tells_activation_targets_apart() {
+        let (notifier, backend) = notifier();
+        let header = Header {
+            id: "chat".to_owned(),
+            title: "Chat".to_owned(),
+            arguments: "openChat".to_owned(),
+        };
+        let (sender, receiver) = mpsc::channel();
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        let toast = toast()
+            .launch("open")
+            .header(&header)
+            .tag("prompt")
+            .on_activation({
+                let handled = handled.clone();
+                move |activation| {
+                    handled.lock().unwrap().push(activation.target);
+                    Ok(())
+                }
+            });
+        notifier
+            .show_with_listener(
+                &toast,
+                Some(Box::new(move |event| sender.send(event).unwrap())),
+            )
+            .unwrap();
+
+        for (arguments, target) in [
+            ("open", ActivationTarget::Body),
+            ("yes", ActivationTarget::Button),
+            ("openChat", ActivationTarget::Header),
+            // from an earlier version of the toast
+            ("other", ActivationTarget::Body),
+        ] {
+            backend.raise(
+                "prompt",
+                ToastEvent::Activated(Activation {
+                    arguments: Some(arguments.to_owned()),
+                    ..Default::default()
+                }),
+            );
+            match receiver.recv().unwrap() {
+                ToastEvent::Activated(activation) => assert_eq!(activation.target, target),
+                event => panic!("expected an activation, got {event:?}"),
+            }
+            assert_eq!(handled.lock().unwrap().last(), Some(&target));
+        }
+        assert_eq!(handled.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn loop_policy_rejects_short_loops() {
+        let (notifier, backend) = notifier();
+        let looping = toast().sound(Some(crate::Sound::Loop(crate::LoopableSound::Alarm)));
+        notifier.show(&looping).unwrap();
+
+        match notifier.show(&looping.clone().loop_policy(LoopPolicy::Reject)) {
+            Err(Error::Violations(violations)) => {
+                assert_eq!(
+                    violations,
+                    [Violation::LoopNeedsLongDuration {
+                        policy: LoopPolicy::Reject
+                    }]
+                );
+                assert_eq!(violations[0].severity(), crate::Severity::Error);
+            }
+            result => panic!("expected violations, got {:?}", result.map(|_| ())),
+        }
+        assert_eq!(backend.shown().len(), 1);
+
+        notifier
+            .show(&looping.loop_policy(LoopPolicy::Promote))
+            .unwrap();
+        assert!(backend.shown()[1]
+            .xml
+            .starts_with(r#"<toast duration="long">"#));
+    }
+
+    #[test]
+    fn renders_for_profile() {
+        let (notifier, backend) = notifier();
+        let notifier = notifier.with_profile(TargetProfile::WINDOWS_8_1);
+        let handle = notifier.show(&toast()).unwrap();
+        assert!(backend.shown()[0].xml.contains(r#"template="ToastText01""#));
+        assert_eq!(
+            handle.downgrades(),
+            [
+                Downgrade::LegacyTemplate {
+                    template: crate::LegacyTemplate::ToastText01
+                },
+                Downgrade::ActionsDropped { count: 2 }
+            ]
+        );
+    }
+
+    #[test]
+    fn in_memory_images_are_cached() {
+        let dir = std::env::temp_dir()
+            .join("tauri-winrt-notification-tests")
+            .join(format!("notifier-{}", std::process::id()));
+        let cache = ImageCache::new(&dir);
+        let (notifier, backend) = notifier();
+        let notifier = notifier.with_image_cache(cache.clone());
+
+        let png = b"\x89PNG\r\n\x1a\nimage";
+        let toast = toast().image(crate::ImageSource::bytes(png), "generated");
+        notifier.show(&toast).unwrap();
+
+        let path = cache.path_of(png);
+        assert_eq!(std::fs::read(&path).unwrap(), png);
+        let uri = crate::ImageSource::from(path).to_uri();
+        assert!(backend.shown()[0].xml.contains(&uri));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn images_of_a_toast_survive_cleaning() {
+        let dir = std::env::temp_dir()
+            .join("tauri-winrt-notification-tests")
+            .join(format!("notifier-clean-{}", std::process::id()));
+        // room for one image only
+        let cache = ImageCache::new(&dir).max_size(15);
+        let (notifier, backend) = notifier();
+        let notifier = notifier.with_image_cache(cache.clone());
+
+        let (first, second) = ([1; 10], [2; 10]);
+        let toast = toast()
+            .image(crate::ImageSource::bytes(&first), "first")
+            .image(crate::ImageSource::bytes(&second), "second");
+        notifier.show(&toast).unwrap();
+        for data in [&first, &second] {
+            let path = cache.path_of(data);
+            assert!(path.exists());
+            let uri = crate::ImageSource::from(path).to_uri();
+            assert!(backend.shown()[0].xml.contains(&uri));
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "remote-images")]
+    #[test]
+    fn remote_images_fall_back() {
+        let dir = std::env::temp_dir()
+            .join("tauri-winrt-notification-tests")
+            .join(format!("notifier-remote-{}", std::process::id()));
+        let cache = ImageCache::new(&dir);
+        let fallback = b"\x89PNG\r\n\x1a\nfallback";
+        let (notifier, backend) = notifier();
+        let notifier = notifier.with_image_cache(cache.clone()).with_remote_images(
+            crate::RemoteImages::new().fallback(crate::ImageSource::bytes(fallback)),
+        );
+
+        // nothing listens on port 1, so the download fails right away
+        let avatar = crate::ImageSource::uri("http://127.0.0.1:1/avatar.png").unwrap();
+        notifier.show(&toast().image(avatar, "avatar")).unwrap();
+
+        let uri = crate::ImageSource::from(cache.path_of(fallback)).to_uri();
+        assert!(backend.shown()[0].xml.contains(&uri));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "remote-images")]
+    #[test]
+    fn downloads_images_of_a_toast_in_parallel() {
+        // accepts connections, but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (notifier, backend) = notifier();
+        let notifier = notifier
+            .with_remote_images(crate::RemoteImages::new().timeout(StdDuration::from_millis(300)));
+
+        let toast = (0..3).fold(toast(), |toast, i| {
+            let image = crate::ImageSource::uri(&format!("{base}/{i}.png")).unwrap();
+            toast.image(image, "silent")
+        });
+        let started = Instant::now();
+        notifier.show(&toast).unwrap();
+        assert!(started.elapsed() < StdDuration::from_millis(600));
+        assert!(backend.shown()[0].xml.contains(&format!("{base}/2.png")));
+    }
+
+    #[cfg(feature = "image-processing")]
+    #[test]
+    fn processes_images() {
+        let dir = std::env::temp_dir()
+            .join("tauri-winrt-notification-tests")
+            .join(format!("notifier-process-{}", std::process::id()));
+        let (notifier, backend) = notifier();
+        let notifier = notifier
+            .with_image_cache(ImageCache::new(&dir))
+            .with_image_processor(crate::ImageProcessor::new());
+
+        let chick =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/test/chick.jpeg");
+        notifier
+            .show(&toast().icon(chick, crate::IconCrop::Circular, "chick"))
+            .unwrap();
+
+        let xml = &backend.shown()[0].xml;
+        assert!(xml.contains(&crate::ImageSource::path(&dir).to_uri()));
+        assert!(!xml.contains("hint-crop"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn hide_removes_from_history() {
+        let (notifier, _backend) = notifier();
+        let dismissed = Arc::new(Mutex::new(None));
+        let toast = toast().tag("hidden").on_dismissed({
+            let dismissed = dismissed.clone();
+            move |reason| {
+                *dismissed.lock().unwrap() = reason;
+                Ok(())
+            }
+        });
+
+        let handle = notifier.show(&toast).unwrap();
+        assert_eq!(handle.tag(), "hidden");
+        handle.hide().unwrap();
+
+        assert!(notifier.history().unwrap().entries().unwrap().is_empty());
+        assert!(!handle.wait_delivered(StdDuration::from_millis(10)).unwrap());
+        assert_eq!(
+            *dismissed.lock().unwrap(),
+            Some(ToastDismissalReason::ApplicationHidden)
+        );
+    }
+
+    #[test]
+    fn removes_by_group() {
+        let (notifier, _backend) = notifier();
+        notifier.show(&toast().tag("ungrouped")).unwrap();
+        let history = notifier.history().unwrap();
+
+        history.remove_grouped("ungrouped", "chat").unwrap();
+        assert_eq!(history.entries().unwrap().len(), 1);
+        history.remove("ungrouped").unwrap();
+        assert!(history.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn failed_toasts_are_not_waited_for() {
+        let (notifier, backend) = notifier();
+        backend.react_with(ToastEvent::Failed(ToastFailure::new(0x80004005_u32 as i32)));
+
+        let handle = notifier.show(&toast()).unwrap();
+        let started = Instant::now();
+        assert!(!handle.wait_delivered(StdDuration::from_secs(5)).unwrap());
+        assert!(started.elapsed() < StdDuration::from_secs(1));
+    }
+
+    #[test]
+    fn delivery_without_action_center() {
+        let (notifier, _backend) = notifier();
+        let notifier = notifier.with_profile(TargetProfile::WINDOWS_8_1);
+
+        let handle = notifier.show(&toast()).unwrap();
+        handle.hide().unwrap();
+        assert!(handle.wait_delivered(StdDuration::from_secs(5)).unwrap());
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Toasts for common layouts, ready to be shown or changed further.
+//!
+//! Every preset is an ordinary [Toast] built with the public builder methods,
+//! and reports its buttons [on_activated](Toast::on_activated) with the actions documented on it.
+//! Presets are pure content without an app id, to be shown through a [Notifier](crate::Notifier).
+//!
+//! ```rust,no_run
+//! use tauri_winrt_notification::{presets, ImageSource, Notifier, Toast};
+//!
+//! let notifier = Notifier::new(Toast::POWERSHELL_APP_ID).expect("notifier unavailable");
+//! let avatar = ImageSource::uri("ms-appx:///alice.png").unwrap();
+//! notifier
+//!     .show(&presets::chat_message("Alice", "Lunch at noon?", Some(avatar)))
+//!     .expect("notification failed");
+//! ```
+
+use crate::{ButtonStyle, IconCrop, ImageSource, Input, Progress, Scenario, Toast};
+
+/// The id of the reply box of [chat_message].
+pub const REPLY_INPUT: &str = "reply";
+/// The id of the snooze time picker of [meeting_reminder], reporting minutes.
+pub const SNOOZE_INPUT: &str = "snoozeMinutes";
+
+/// A chat message from `sender`, with a circular avatar and a box to reply right away.
+///
+/// The `Send` button reports `reply`, with the reply as [REPLY_INPUT].
+pub fn chat_message(sender: &str, message: &str, avatar: Option<ImageSource>) -> Toast {
+    let mut toast = Toast::default().title(sender).text1(message);
+    if let Some(avatar) = avatar {
+        toast = toast.icon(avatar, IconCrop::Circular, sender);
+    }
+    toast
+        .add_input(Input::text(REPLY_INPUT).placeholder("Type a reply"))
+        .add_button("Send", "reply")
+}
+
+/// A download of `file_name` with a progress bar, updated through [Toast::set_progress] with `tag`.
+///
+/// The `Open` and `Cancel` buttons report `open` and `cancel`.
+pub fn download(file_name: &str, tag: &str) -> Toast {
+    let progress = Progress {
+        tag: tag.to_owned(),
+        title: file_name.to_owned(),
+        status: "Downloading...".to_owned(),
+        value: 0.0,
+        value_string: "0%".to_owned(),
+    };
+    Toast::default()
+        .title("Downloading")
+        .progress(&progress)
+        .add_button("Open", "open")
+        .add_button("Cancel", "cancel")
+}
+
+/// A reminder of the meeting `subject` starting at `when`, which stays on screen until handled.
+///
+/// The `Snooze` button reports `snooze`, with the picked minutes as [SNOOZE_INPUT],
+/// the `Dismiss` button reports `dismiss`.
+pub fn meeting_reminder(subject: &str, when: &str, location: Option<&str>) -> Toast {
+    let mut toast = Toast::default()
+        .scenario(Scenario::Reminder)
+        .title(subject)
+        .text1(when);
+    if let Some(location) = location {
+        toast = toast.text2(location);
+    }
+    toast
+        .add_input(
+            Input::selection(
+                SNOOZE_INPUT,
+                &[("5", "5 minutes"), ("15", "15 minutes"), ("60", "1 hour")],
+            )
+            .default_value("5"),
+        )
+        .add_button("Snooze", "snooze")
+        .add_button("Dismiss", "dismiss")
+}
+
+/// Whether a build passed, see [build_result].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildOutcome {
+    Passed,
+    Failed,
+}
+
+/// The result of the build `name`, with `summary` below it.
+///
+/// A passed build has a green `Open` button reporting `open`, a failed build a red `View log`
+/// button reporting `log` and a `Rerun` button reporting `rerun`.
+pub fn build_result(name: &str, outcome: BuildOutcome, summary: &str) -> Toast {
+    let toast = Toast::default().text1(summary);
+    match outcome {
+        BuildOutcome::Passed => toast.title(&format!("{name} passed")).add_styled_button(
+            "Open",
+            "open",
+            ButtonStyle::Success,
+        ),
+        BuildOutcome::Failed => toast
+            .title(&format!("{name} failed"))
+            .add_styled_button("View log", "log", ButtonStyle::Critical)
+            .add_button("Rerun", "rerun"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rendered, TargetProfile};
+
+    fn xml(toast: Toast) -> String {
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_11);
+        assert_eq!(downgrades, []);
+        assert_eq!(toast.validate_for(TargetProfile::WINDOWS_11), []);
+        xml
+    }
+
+    #[test]
+    fn chat_message_snapshot() {
+        let avatar = ImageSource::uri("ms-appx:///alice.png").unwrap();
+        assert_eq!(
+            xml(chat_message("Alice", "Lunch at noon?", Some(avatar))),
+            concat!(
+                r#"<toast><visual><binding template="ToastGeneric">"#,
+                r#"<image placement="appLogoOverride" hint-crop="circle" src="ms-appx:///alice.png" alt="Alice"/>"#,
+                r#"<text id="1">Alice</text><text id="2">Lunch at noon?</text>"#,
+                r#"</binding></visual>"#,
+                r#"<actions><input id="reply" type="text" placeHolderContent="Type a reply"/>"#,
+                r#"<action content='Send' arguments='reply'/></actions>"#,
+                r#"</toast>"#
+            )
+        );
+    }
+
+    #[test]
+    fn download_snapshot() {
+        assert_eq!(
+            xml(download("report.pdf", "download-1")),
+            concat!(
+                r#"<toast><visual><binding template="ToastGeneric">"#,
+                r#"<text id="1">Downloading</text>"#,
+                r#"<progress title="{progressTitle}" value="{progressValue}" "#,
+                r#"valueStringOverride="{progressValueString}" status="{progressStatus}"/>"#,
+                r#"</binding></visual>"#,
+                r#"<actions><action content='Open' arguments='open'/>"#,
+                r#"<action content='Cancel' arguments='cancel'/></actions>"#,
+                r#"</toast>"#
+            )
+        );
+    }
+
+    #[test]
+    fn meeting_reminder_snapshot() {
+        assert_eq!(
+            xml(meeting_reminder("Standup", "10:00 - 10:15", Some("Room 4"))),
+            concat!(
+                r#"<toast scenario="reminder"><visual><binding template="ToastGeneric">"#,
+                r#"<text id="1">Standup</text><text id="2">10:00 - 10:15</text><text id="3">Room 4</text>"#,
+                r#"</binding></visual>"#,
+                r#"<actions><input id="snoozeMinutes" type="selection" defaultInput="5">"#,
+                r#"<selection id="5" content="5 minutes"/><selection id="15" content="15 minutes"/>"#,
+                r#"<selection id="60" content="1 hour"/></input>"#,
+                r#"<action content='Snooze' arguments='snooze'/>"#,
+                r#"<action content='Dismiss' arguments='dismiss'/></actions>"#,
+                r#"</toast>"#
+            )
+        );
+    }
+
+    #[test]
+    fn build_result_snapshots() {
+        assert_eq!(
+            xml(build_result("nightly", BuildOutcome::Passed, "312 tests")),
+            concat!(
+                r#"<toast useButtonStyle="true"><visual><binding template="ToastGeneric">"#,
+                r#"<text id="1">nightly passed</text><text id="2">312 tests</text>"#,
+                r#"</binding></visual>"#,
+                r#"<actions><action content='Open' arguments='open' hint-buttonStyle="Success"/></actions>"#,
+                r#"</toast>"#
+            )
+        );
+        assert_eq!(
+            xml(build_result(
+                "nightly",
+                BuildOutcome::Failed,
+                "3 of 312 tests failed"
+            )),
+            concat!(
+                r#"<toast useButtonStyle="true"><visual><binding template="ToastGeneric">"#,
+                r#"<text id="1">nightly failed</text><text id="2">3 of 312 tests failed</text>"#,
+                r#"</binding></visual>"#,
+                r#"<actions><action content='View log' arguments='log' hint-buttonStyle="Critical"/>"#,
+                r#"<action content='Rerun' arguments='rerun'/></actions>"#,
+                r#"</toast>"#
+            )
+        );
+    }
+}
@@ -0,0 +1,388 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Resizing, cropping and converting images to what toasts show best.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageOutputFormat, Rgba, RgbaImage};
+
+use crate::{
+    dimensions, Error, IconCrop, Image, ImageCache, ImageOptions, ImagePlacement, ImageSource,
+    Result,
+};
+
+/// The size of app logos, 48 pixels at the largest scale factor of 400%.
+const APP_LOGO_SIZE: u32 = 192;
+/// The largest hero image, 364 by 180 pixels at 400%.
+const HERO_SIZE: (u32, u32) = (1456, 720);
+/// The largest inline image, 364 pixels wide at 400%.
+const INLINE_SIZE: (u32, u32) = (1456, 1456);
+
+/// The formats Windows shows on toasts.
+const SHOWN_FORMATS: [ImageFormat; 3] = [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::Gif];
+
+/// Prepares images before a toast is shown, so they look the same on every version of Windows.
+///
+/// * Images larger than toasts show them are scaled down, which also keeps them within
+///   the size limits that make Windows leave images out.
+/// * [Circular](IconCrop::Circular) icons and [circle cropped](ImageOptions::circle_crop) images are
+///   cropped to transparent PNGs, because Windows 8.1 ignores the crop. They are shown uncropped afterwards.
+/// * WebP, BMP, ICO and the other formats Windows does not show are converted to PNG.
+///
+/// Every step can be turned off. Processed images are written to the [ImageCache] of the
+/// [Notifier](crate::Notifier), and images that cannot be read or decoded are shown as they are.
+/// Images of different toasts are processed in parallel, so an image shown again while it is
+/// still being processed may be processed twice.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::path::Path;
+/// use tauri_winrt_notification::{IconCrop, ImageProcessor, Notifier, Toast};
+///
+/// let notifier = Notifier::new(Toast::POWERSHELL_APP_ID)
+///     .expect("notifier unavailable")
+///     .with_image_processor(ImageProcessor::new());
+///
+/// let toast = Toast::new(Toast::POWERSHELL_APP_ID)
+///     .icon(Path::new(r"C:\Users\me\Pictures\avatar.webp"), IconCrop::Circular, "avatar");
+/// notifier.show(&toast).expect("notification failed");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ImageProcessor {
+    resize: bool,
+    crop_circles: bool,
+    convert: bool,
+    processed: Arc<Mutex<Processed>>,
+}
+
+/// The processed file of every image and plan, keyed by the cache path of the original image,
+/// `None` if the image was shown as it is.
+type Processed = HashMap<(PathBuf, Plan), Option<PathBuf>>;
+
+/// What is done to a single image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Plan {
+    bounds: Option<(u32, u32)>,
+    circle: bool,
+    convert: bool,
+}
+
+impl ImageProcessor {
+    /// A processor that resizes, crops and converts.
+    pub fn new() -> ImageProcessor {
+        ImageProcessor {
+            resize: true,
+            crop_circles: true,
+            convert: true,
+            processed: Default::default(),
+        }
+    }
+
+    /// Set whether images larger than toasts show them are scaled down.
+    pub fn resize(mut self, resize: bool) -> ImageProcessor {
+        self.resize = resize;
+        self
+    }
+
+    /// Set whether circular icons are cropped instead of leaving it to Windows.
+    pub fn crop_circles(mut self, crop_circles: bool) -> ImageProcessor {
+        self.crop_circles = crop_circles;
+        self
+    }
+
+    /// Set whether formats Windows does not show are converted to PNG.
+    pub fn convert(mut self, convert: bool) -> ImageProcessor {
+        self.convert = convert;
+        self
+    }
+
+    /// The image to show instead of `image`, with its processed data written to `cache`.
+    pub(crate) fn process(&self, image: &Image, cache: &ImageCache) -> Image {
+        let plan = self.plan(image);
+        let data = match (image.source.as_bytes(), image.source.as_path()) {
+            (Some(data), _) => data.to_vec(),
+            (None, Some(path)) => match std::fs::read(path) {
+                Ok(data) => data,
+                Err(_) => return image.clone(),
+            },
+            (None, None) => return image.clone(),
+        };
+
+        let key = (cache.path_of(&data), plan);
+        let known = self.processed.lock().unwrap().get(&key).cloned();
+        let path = match known {
+            Some(path) if path.as_ref().map_or(true, |path| path.is_file()) => path,
+            _ => {
+                // not locked while processing, so a large image does not hold up other toasts
+                let path = match apply(&data, plan) {
+                    Ok(Some(data)) => cache.store(&data).ok(),
+                    Ok(None) | Err(_) => None,
+                };
+                self.processed.lock().unwrap().insert(key, path.clone());
+                path
+            }
+        };
+
+        match path {
+            Some(path) => Image {
+                placement: match image.placement {
+                    ImagePlacement::AppLogo(_) if plan.circle => {
+                        ImagePlacement::AppLogo(IconCrop::Square)
+                    }
+                    ref placement => placement.clone(),
+                },
+                source: ImageSource::path(&path),
+                options: ImageOptions {
+                    circle_crop: image.options.circle_crop && !plan.circle,
+                    ..image.options.clone()
+                },
+                ..image.clone()
+            },
+            None => image.clone(),
+        }
+    }
+
+    fn plan(&self, image: &Image) -> Plan {
+        let (bounds, circle) = match &image.placement {
+            ImagePlacement::AppLogo(crop) => {
+                ((APP_LOGO_SIZE, APP_LOGO_SIZE), *crop == IconCrop::Circular)
+            }
+            ImagePlacement::Hero => (HERO_SIZE, false),
+            ImagePlacement::Inline => (INLINE_SIZE, image.options.circle_crop),
+        };
+        Plan {
+            bounds: self.resize.then_some(bounds),
+            circle: circle && self.crop_circles,
+            convert: self.convert,
+        }
+    }
+}
+
+impl Default for ImageProcessor {
+    fn default() -> Self {
+        ImageProcessor::new()
+    }
+}
+
+/// Process `data` according to `plan`, `None` if it can be shown as it is.
+fn apply(data: &[u8], plan: Plan) -> Result<Option<Vec<u8>>> {
+    let format = image::guess_format(data).map_err(invalid)?;
+    let shown = SHOWN_FORMATS.contains(&format);
+    let fits = |(width, height): (u32, u32)| {
+        plan.bounds.map_or(true, |(max_width, max_height)| {
+            width <= max_width && height <= max_height
+        })
+    };
+    if !shown && !plan.convert || !plan.circle && shown && dimensions::parse(data).is_some_and(fits)
+    {
+        return Ok(None);
+    }
+
+    let mut image = image::load_from_memory_with_format(data, format).map_err(invalid)?;
+    if plan.circle {
+        let side = image.width().min(image.height());
+        image = image.crop_imm(
+            (image.width() - side) / 2,
+            (image.height() - side) / 2,
+            side,
+            side,
+        );
+    }
+    if !fits((image.width(), image.height())) {
+        let (width, height) = plan.bounds.unwrap_or_default();
+        image = image.resize(width, height, FilterType::Lanczos3);
+    }
+    if plan.circle {
+        image = DynamicImage::ImageRgba8(circle(image.to_rgba8()));
+    }
+
+    // photos stay JPEG, everything else becomes PNG to keep transparency
+    let output = if format == ImageFormat::Jpeg && !plan.circle {
+        ImageOutputFormat::Jpeg(90)
+    } else {
+        ImageOutputFormat::Png
+    };
+    let mut encoded = Cursor::new(Vec::new());
+    image.write_to(&mut encoded, output).map_err(invalid)?;
+    Ok(Some(encoded.into_inner()))
+}
+
+/// Make everything outside the circle inscribed in `image` transparent, with anti-aliased edges.
+fn circle(mut image: RgbaImage) -> RgbaImage {
+    let radius = image.width().min(image.height()) as f32 / 2.0;
+    let (center_x, center_y) = (image.width() as f32 / 2.0, image.height() as f32 / 2.0);
+    for (x, y, Rgba([_, _, _, alpha])) in image.enumerate_pixels_mut() {
+        let distance = (x as f32 + 0.5 - center_x).hypot(y as f32 + 0.5 - center_y);
+        let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+        *alpha = (*alpha as f32 * coverage).round() as u8;
+    }
+    image
+}
+
+fn invalid(error: image::ImageError) -> Error {
+    Error::InvalidImage(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(image: RgbaImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut encoded = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(image)
+            .write_to(&mut encoded, format)
+            .unwrap();
+        encoded.into_inner()
+    }
+
+    fn plan(bounds: Option<(u32, u32)>, circle: bool) -> Plan {
+        Plan {
+            bounds,
+            circle,
+            convert: true,
+        }
+    }
+
+    fn image(name: &str, placement: ImagePlacement) -> Image {
+        Image {
+            placement,
+            source: ImageSource::path(
+                &PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                    .join("resources/test")
+                    .join(name),
+            ),
+            alt_text: "alt".to_owned(),
+            options: Default::default(),
+        }
+    }
+
+    fn cache(name: &str) -> ImageCache {
+        ImageCache::new(
+            std::env::temp_dir()
+                .join("tauri-winrt-notification-tests")
+                .join(format!("{name}-{}", std::process::id())),
+        )
+    }
+
+    #[test]
+    fn leaves_fitting_images_alone() {
+        let png = encode(RgbaImage::new(16, 16), ImageOutputFormat::Png);
+        assert_eq!(apply(&png, plan(Some((32, 32)), false)).unwrap(), None);
+        assert_eq!(apply(&png, plan(None, false)).unwrap(), None);
+    }
+
+    #[test]
+    fn resizes_within_bounds() {
+        let png = encode(RgbaImage::new(400, 100), ImageOutputFormat::Png);
+        let resized = apply(&png, plan(Some((200, 200)), false)).unwrap().unwrap();
+        assert_eq!(dimensions::parse(&resized), Some((200, 50)));
+    }
+
+    #[test]
+    fn keeps_photos_jpeg() {
+        let jpeg = include_bytes!("../resources/test/chick.jpeg");
+        let resized = apply(jpeg, plan(Some((144, 144)), false)).unwrap().unwrap();
+        assert_eq!(image::guess_format(&resized).unwrap(), ImageFormat::Jpeg);
+        assert_eq!(dimensions::parse(&resized), Some((144, 96)));
+    }
+
+    #[test]
+    fn converts_unsupported_formats() {
+        let bmp = encode(RgbaImage::new(8, 8), ImageOutputFormat::Bmp);
+        let converted = apply(&bmp, plan(None, false)).unwrap().unwrap();
+        assert_eq!(image::guess_format(&converted).unwrap(), ImageFormat::Png);
+
+        let unconverted = Plan {
+            convert: false,
+            ..plan(None, false)
+        };
+        assert_eq!(apply(&bmp, unconverted).unwrap(), None);
+    }
+
+    #[test]
+    fn crops_circles() {
+        let opaque = RgbaImage::from_pixel(60, 40, Rgba([255, 0, 0, 255]));
+        let png = encode(opaque, ImageOutputFormat::Png);
+        let cropped = apply(&png, plan(Some((192, 192)), true)).unwrap().unwrap();
+        let cropped = image::load_from_memory(&cropped).unwrap().to_rgba8();
+
+        assert_eq!(cropped.dimensions(), (40, 40));
+        assert_eq!(cropped.get_pixel(20, 20).0, [255, 0, 0, 255]);
+        assert_eq!(cropped.get_pixel(0, 0).0[3], 0);
+        assert_eq!(cropped.get_pixel(39, 39).0[3], 0);
+        // anti-aliased edge
+        assert!(cropped
+            .pixels()
+            .any(|pixel| pixel.0[3] > 0 && pixel.0[3] < 255));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(matches!(
+            apply(b"not an image", plan(None, false)),
+            Err(Error::InvalidImage(_))
+        ));
+    }
+
+    #[test]
+    fn processes_into_the_cache() {
+        let cache = cache("process");
+        let processor = ImageProcessor::new();
+
+        let icon = image("chick.jpeg", ImagePlacement::AppLogo(IconCrop::Circular));
+        let processed = processor.process(&icon, &cache);
+        assert!(matches!(
+            processed.placement,
+            ImagePlacement::AppLogo(IconCrop::Square)
+        ));
+        let path = processed.source.as_path().unwrap();
+        assert!(path.starts_with(cache.dir()));
+        assert_eq!(dimensions::read(path), Some((192, 192)));
+        assert_eq!(processor.process(&icon, &cache).source, processed.source);
+
+        let hero = image("chick.jpeg", ImagePlacement::Hero);
+        assert_eq!(processor.process(&hero, &cache).source, hero.source);
+
+        let mut caller = image("chick.jpeg", ImagePlacement::Inline);
+        caller.options.circle_crop = true;
+        let processed = processor.process(&caller, &cache);
+        assert_ne!(processed.source, caller.source);
+        assert!(!processed.options.circle_crop);
+
+        let missing = image("missing.png", ImagePlacement::Inline);
+        assert_eq!(processor.process(&missing, &cache).source, missing.source);
+
+        std::fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn processes_in_parallel() {
+        let cache = cache("process-parallel");
+        let processor = ImageProcessor::new();
+
+        // small to hash, slow to resize
+        let png = encode(RgbaImage::new(2000, 2000), ImageOutputFormat::Png);
+        let large = Image {
+            source: ImageSource::bytes(&png),
+            ..image("chick.jpeg", ImagePlacement::Hero)
+        };
+        let (large_processor, large_cache) = (processor.clone(), cache.clone());
+        let large = std::thread::spawn(move || large_processor.process(&large, &large_cache));
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        let started = std::time::Instant::now();
+        let hero = image("chick.jpeg", ImagePlacement::Hero);
+        assert_eq!(processor.process(&hero, &cache).source, hero.source);
+        assert!(started.elapsed() < std::time::Duration::from_millis(400));
+        assert!(large.join().unwrap().source.as_path().is_some());
+
+        std::fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::sync::OnceLock;
+
+/// The version of Windows a toast is rendered for.
+///
+/// Decides which parts of a toast Windows can show, everything else is downgraded or dropped.
+/// [Notifier] uses the profile of the running system unless told otherwise with [Notifier::with_profile],
+/// [Toast::to_xml] renders a toast for any profile.
+///
+/// [Notifier]: crate::Notifier
+/// [Notifier::with_profile]: crate::Notifier::with_profile
+/// [Toast::to_xml]: crate::Toast::to_xml
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TargetProfile {
+    build: u32,
+}
+
+/// A part of the toast schema that only some versions of Windows support.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// The `ToastGeneric` template, older versions only have the legacy templates.
+    AdaptiveTemplate,
+    /// Replacing the app logo with an [icon](crate::Toast::icon).
+    AppLogoOverride,
+    /// Cropping the icon to a circle.
+    CircleCrop,
+    /// [Hero](crate::Toast::hero) images.
+    Hero,
+    /// Attribution text.
+    Attribution,
+    /// [Progress](crate::Toast::progress) bars.
+    Progress,
+    /// Buttons and [inputs](crate::Toast::add_input).
+    Actions,
+    /// [Headers](crate::Toast::header) in the action center.
+    Header,
+    /// Reporting the [user input](crate::Activation::user_input) on activation.
+    ActivationUserInput,
+    /// Playing [custom sounds](crate::Sound::Custom) in desktop apps.
+    CustomAudio,
+    /// Centered texts on [incoming calls](crate::Scenario::IncomingCall).
+    CallLayout,
+    /// The [urgent](crate::Scenario::Urgent) scenario.
+    UrgentScenario,
+    /// [Colored buttons](crate::Toast::add_styled_button).
+    ButtonStyle,
+    /// Opening a [protocol URI](crate::ProtocolUri::target_application) with a specific app.
+    ProtocolTarget,
+    /// The toasts in the action center, read through [Notifier::history](crate::Notifier::history).
+    History,
+}
+
+impl Capability {
+    /// The first build of Windows with the capability.
+    pub fn since(&self) -> TargetProfile {
+        match self {
+            Capability::AdaptiveTemplate
+            | Capability::AppLogoOverride
+            | Capability::Actions
+            | Capability::History => TargetProfile::WINDOWS_10,
+            Capability::CircleCrop
+            | Capability::Hero
+            | Capability::Attribution
+            | Capability::Progress => TargetProfile::ANNIVERSARY_UPDATE,
+            Capability::Header | Capability::ProtocolTarget => TargetProfile::CREATORS_UPDATE,
+            Capability::ActivationUserInput | Capability::CustomAudio => {
+                TargetProfile::FALL_CREATORS_UPDATE
+            }
+            Capability::CallLayout | Capability::ButtonStyle => TargetProfile::WINDOWS_11,
+            Capability::UrgentScenario => TargetProfile::WINDOWS_11_22H2,
+        }
+    }
+}
+
+impl TargetProfile {
+    /// Windows 8.1, only the legacy templates with a single image.
+    pub const WINDOWS_8_1: TargetProfile = TargetProfile::from_build(9600);
+    /// The original release of Windows 10, version 1507.
+    pub const WINDOWS_10: TargetProfile = TargetProfile::from_build(10240);
+    /// The Windows 10 Anniversary Update, version 1607.
+    pub const ANNIVERSARY_UPDATE: TargetProfile = TargetProfile::from_build(14393);
+    /// The Windows 10 Creators Update, version 1703.
+    pub const CREATORS_UPDATE: TargetProfile = TargetProfile::from_build(15063);
+    /// The Windows 10 Fall Creators Update, version 1709.
+    pub const FALL_CREATORS_UPDATE: TargetProfile = TargetProfile::from_build(16299);
+    /// The original release of Windows 11, version 21H2.
+    pub const WINDOWS_11: TargetProfile = TargetProfile::from_build(22000);
+    /// The Windows 11 2022 Update, version 22H2.
+    pub const WINDOWS_11_22H2: TargetProfile = TargetProfile::from_build(22621);
+
+    /// The profile of a Windows build number, like `19045`.
+    pub const fn from_build(build: u32) -> TargetProfile {
+        TargetProfile { build }
+    }
+
+    /// The Windows build number.
+    pub fn build(&self) -> u32 {
+        self.build
+    }
+
+    /// The profile of the running system.
+    ///
+    /// The version is only queried once. On platforms other than Windows this is [WINDOWS_11](Self::WINDOWS_11),
+    /// so the output does not depend on the host.
+    pub fn detect() -> TargetProfile {
+        static DETECTED: OnceLock<TargetProfile> = OnceLock::new();
+        *DETECTED.get_or_init(detect)
+    }
+
+    /// Whether Windows supports `capability` from this profile on.
+    pub fn supports(&self, capability: Capability) -> bool {
+        *self >= capability.since()
+    }
+}
+
+impl Default for TargetProfile {
+    /// The [detected](Self::detect) profile.
+    fn default() -> Self {
+        TargetProfile::detect()
+    }
+}
+
+#[cfg(windows)]
+fn detect() -> TargetProfile {
+    let os = windows_version::OsVersion::current();
+    if os.major > 6 {
+        TargetProfile::from_build(os.build)
+    } else {
+        // Windows 8 does not have a separate profile, the 8.1 templates are the same
+        TargetProfile::from_build(os.build.min(TargetProfile::WINDOWS_8_1.build))
+    }
+}
+
+#[cfg(not(windows))]
+fn detect() -> TargetProfile {
+    TargetProfile::WINDOWS_11
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities() {
+        let profile = TargetProfile::WINDOWS_8_1;
+        assert!(!profile.supports(Capability::AdaptiveTemplate));
+        assert!(!profile.supports(Capability::Actions));
+        assert!(!profile.supports(Capability::History));
+
+        let profile = TargetProfile::WINDOWS_10;
+        assert!(profile.supports(Capability::AdaptiveTemplate));
+        assert!(!profile.supports(Capability::CircleCrop));
+        assert!(!profile.supports(Capability::Hero));
+
+        let profile = TargetProfile::from_build(15063);
+        assert!(profile.supports(Capability::Hero));
+        assert!(profile.supports(Capability::Progress));
+        assert!(profile.supports(Capability::Header));
+        assert!(!profile.supports(Capability::ActivationUserInput));
+
+        assert!(TargetProfile::WINDOWS_11.supports(Capability::ActivationUserInput));
+        assert!(TargetProfile::WINDOWS_11.supports(Capability::CallLayout));
+        assert!(!TargetProfile::WINDOWS_11.supports(Capability::UrgentScenario));
+        assert!(TargetProfile::WINDOWS_11_22H2.supports(Capability::UrgentScenario));
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(TargetProfile::WINDOWS_8_1 < TargetProfile::WINDOWS_10);
+        assert!(TargetProfile::FALL_CREATORS_UPDATE < TargetProfile::WINDOWS_11);
+        assert_eq!(TargetProfile::from_build(22000), TargetProfile::WINDOWS_11);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn detect_is_deterministic() {
+        assert_eq!(TargetProfile::detect(), TargetProfile::WINDOWS_11);
+    }
+}
@@ -0,0 +1,304 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! URIs opened by protocol activation, and the rules they are checked against.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::source::scheme;
+use crate::{Error, Result};
+
+/// The schemes [ProtocolUri::new] allows.
+const PROTOCOL_SCHEMES: [&str; 7] = [
+    "http",
+    "https",
+    "mailto",
+    "tel",
+    "sms",
+    "ms-settings",
+    "ms-windows-store",
+];
+
+/// Schemes that are never opened from a toast, because they run code or open local files.
+const BLOCKED_SCHEMES: [&str; 4] = ["javascript", "vbscript", "data", "file"];
+
+/// The longest URI opened from a toast, the limit of Windows for URLs.
+pub const MAX_PROTOCOL_URI_LENGTH: usize = 2048;
+
+/// A URI opened with the app registered for its scheme when a toast or button is clicked,
+/// see [Toast::add_protocol_button](crate::Toast::add_protocol_button) and
+/// [Toast::launch_protocol](crate::Toast::launch_protocol).
+///
+/// URIs are checked and normalized when created:
+///
+/// * Surrounding whitespace is trimmed, control characters are not allowed.
+/// * The scheme must be allowed, and is lowercased. `javascript`, `vbscript`, `data` and `file` are never allowed.
+/// * `http` and `https` URIs need a host, which is lowercased.
+/// * Characters not allowed in URIs, like spaces and non-ASCII letters, are percent-encoded.
+/// * The normalized URI is at most [MAX_PROTOCOL_URI_LENGTH] bytes long.
+///
+/// # Example
+/// ```rust,no_run
+/// use tauri_winrt_notification::{ProtocolUri, Toast};
+///
+/// let release = ProtocolUri::new("https://github.com/tauri-apps/winrt-notification/releases").unwrap();
+/// let settings = ProtocolUri::new("ms-settings:notifications").unwrap();
+/// Toast::new(Toast::POWERSHELL_APP_ID)
+///     .title("Update available")
+///     .launch_protocol(&release)
+///     .add_protocol_button("Notification settings", &settings)
+///     .show()
+///     .expect("notification failed");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProtocolUri {
+    uri: String,
+    target_application: Option<String>,
+}
+
+impl ProtocolUri {
+    /// A URI with one of the schemes `http`, `https`, `mailto`, `tel`, `sms`, `ms-settings` or `ms-windows-store`.
+    ///
+    /// Fails with [Error::InvalidUri] if the URI breaks any of the rules above.
+    pub fn new(uri: &str) -> Result<ProtocolUri> {
+        ProtocolUri::with_schemes(uri, &PROTOCOL_SCHEMES)
+    }
+
+    /// A URI with one of `schemes`, like the scheme an app registered for deep links into itself.
+    ///
+    /// Fails with [Error::InvalidUri] if the URI breaks any of the rules above.
+    pub fn with_schemes(uri: &str, schemes: &[&str]) -> Result<ProtocolUri> {
+        let uri = normalize(uri)?;
+        let scheme = scheme(&uri).unwrap_or_default();
+        if !schemes
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+        {
+            return Err(invalid(
+                &uri,
+                format!("`{scheme}` is not an allowed scheme"),
+            ));
+        }
+        Ok(ProtocolUri {
+            uri,
+            target_application: None,
+        })
+    }
+
+    /// Open the URI with the app with the package family name `pfn`,
+    /// instead of the default app for the scheme.
+    ///
+    /// Requires the Windows 10 Creators Update, older versions open the default app.
+    pub fn target_application(mut self, pfn: &str) -> ProtocolUri {
+        self.target_application = Some(pfn.to_owned());
+        self
+    }
+
+    /// The normalized URI.
+    pub fn as_str(&self) -> &str {
+        &self.uri
+    }
+
+    /// The package family name of the app opening the URI, if set.
+    pub fn target(&self) -> Option<&str> {
+        self.target_application.as_deref()
+    }
+}
+
+impl FromStr for ProtocolUri {
+    type Err = Error;
+
+    /// Parses URIs with the schemes [ProtocolUri::new] allows.
+    fn from_str(s: &str) -> Result<Self> {
+        ProtocolUri::new(s)
+    }
+}
+
+impl Display for ProtocolUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.uri)
+    }
+}
+
+/// Check `uri` against every rule but the allowed schemes, and normalize it.
+pub(crate) fn normalize(uri: &str) -> Result<String> {
+    let trimmed = uri.trim();
+    if trimmed.chars().any(char::is_control) {
+        return Err(invalid(uri, "control characters are not allowed"));
+    }
+    let scheme = scheme(trimmed).ok_or_else(|| invalid(uri, "missing scheme"))?;
+    let scheme = scheme.to_ascii_lowercase();
+    if BLOCKED_SCHEMES.contains(&scheme.as_str()) {
+        return Err(invalid(
+            uri,
+            format!("`{scheme}` URIs cannot be opened from toasts"),
+        ));
+    }
+
+    let rest = &trimmed[scheme.len() + 1..];
+    let rest = if scheme == "http" || scheme == "https" {
+        let authority = rest
+            .strip_prefix("//")
+            .map(|after| after.split(['/', '?', '#']).next().unwrap_or_default())
+            .filter(|authority| !authority.is_empty())
+            .ok_or_else(|| invalid(uri, "missing host"))?;
+        let path = &rest[2 + authority.len()..];
+        format!("//{}{}", authority.to_lowercase(), path)
+    } else {
+        rest.to_owned()
+    };
+
+    let normalized = format!("{scheme}:{}", encode(&rest));
+    if normalized.len() > MAX_PROTOCOL_URI_LENGTH {
+        return Err(invalid(
+            uri,
+            format!("longer than {MAX_PROTOCOL_URI_LENGTH} characters"),
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Percent-encode the characters not allowed anywhere in a URI, keeping valid escapes.
+fn encode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut encoded = String::with_capacity(uri.len());
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escape = byte == b'%'
+            && bytes.len() > i + 2
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit();
+        match byte {
+            b'%' if escape => encoded.push('%'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b':' | b'/' | b'?' | b'#' | b'[' | b']' | b'@' => {
+                encoded.push(byte as char)
+            }
+            b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn invalid(uri: &str, reason: impl Into<String>) -> Error {
+    Error::InvalidUri {
+        uri: uri.to_owned(),
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reason(result: Result<ProtocolUri>) -> String {
+        match result {
+            Err(Error::InvalidUri { reason, .. }) => reason,
+            result => panic!("expected an invalid URI, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn normalizes() {
+        for (uri, normalized) in [
+            (
+                "https://example.com/a?b=c&d=e#f",
+                "https://example.com/a?b=c&d=e#f",
+            ),
+            ("  HTTPS://Example.COM/Path ", "https://example.com/Path"),
+            ("https://example.com", "https://example.com"),
+            (
+                "https://example.com/release notes",
+                "https://example.com/release%20notes",
+            ),
+            (
+                "https://example.com/caf%C3%A9",
+                "https://example.com/caf%C3%A9",
+            ),
+            ("https://example.com/café", "https://example.com/caf%C3%A9"),
+            ("https://example.com/100%", "https://example.com/100%25"),
+            ("mailto:alice@example.com", "mailto:alice@example.com"),
+            ("ms-settings:notifications", "ms-settings:notifications"),
+            ("TEL:+1-555-0100", "tel:+1-555-0100"),
+        ] {
+            assert_eq!(ProtocolUri::new(uri).unwrap().as_str(), normalized);
+        }
+    }
+
+    #[test]
+    fn allows_schemes() {
+        assert_eq!(
+            reason(ProtocolUri::new("myapp://chat/42")),
+            "`myapp` is not an allowed scheme"
+        );
+        let deep_link = ProtocolUri::with_schemes("MyApp://chat/42", &["myapp"]).unwrap();
+        assert_eq!(deep_link.as_str(), "myapp://chat/42");
+        assert_eq!(
+            reason(ProtocolUri::with_schemes("https://example.com", &["myapp"])),
+            "`https` is not an allowed scheme"
+        );
+    }
+
+    #[test]
+    fn blocks_schemes() {
+        for uri in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            "vbscript:msgbox",
+            "data:text/html,hi",
+            "file:///C:/Windows/System32/calc.exe",
+        ] {
+            let scheme = uri.split(':').next().unwrap().to_ascii_lowercase();
+            assert_eq!(
+                reason(ProtocolUri::with_schemes(uri, &[&scheme])),
+                format!("`{scheme}` URIs cannot be opened from toasts")
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_uris() {
+        assert_eq!(reason(ProtocolUri::new("example.com")), "missing scheme");
+        assert_eq!(reason(ProtocolUri::new("")), "missing scheme");
+        assert_eq!(
+            reason(ProtocolUri::new("https:example.com")),
+            "missing host"
+        );
+        assert_eq!(reason(ProtocolUri::new("https:///path")), "missing host");
+        assert_eq!(
+            reason(ProtocolUri::new("https://example.com/\nx")),
+            "control characters are not allowed"
+        );
+    }
+
+    #[test]
+    fn limits_length() {
+        let base = "https://example.com/";
+        let longest = format!("{base}{}", "a".repeat(MAX_PROTOCOL_URI_LENGTH - base.len()));
+        assert!(ProtocolUri::new(&longest).is_ok());
+        assert_eq!(
+            reason(ProtocolUri::new(&format!("{longest}a"))),
+            "longer than 2048 characters"
+        );
+        // measured after encoding
+        let spaces = format!("{base}{}", "a b".repeat(500));
+        assert_eq!(
+            reason(ProtocolUri::new(&spaces)),
+            "longer than 2048 characters"
+        );
+    }
+
+    #[test]
+    fn target_application() {
+        let uri: ProtocolUri = "https://example.com".parse().unwrap();
+        assert_eq!(uri.target(), None);
+        let uri = uri.target_application("Microsoft.WindowsStore_8wekyb3d8bbwe");
+        assert_eq!(uri.target(), Some("Microsoft.WindowsStore_8wekyb3d8bbwe"));
+        assert_eq!(uri.to_string(), "https://example.com");
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Downloading `http(s)` images for apps that Windows does not load them for.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration as StdDuration, Instant};
+
+use crate::{Error, ImageCache, ImageSource, Result};
+
+// the stub server tests run against `winrt` on the Windows CI jobs and `plain` elsewhere
+#[cfg(all(test, not(windows)))]
+mod plain;
+#[cfg(windows)]
+mod winrt;
+
+#[cfg(all(test, not(windows)))]
+use plain::get;
+#[cfg(windows)]
+use winrt::get;
+
+/// Toasts are never shown on platforms other than Windows, so nothing is downloaded there.
+#[cfg(all(not(test), not(windows)))]
+fn get(
+    url: &str,
+    _deadline: Instant,
+    _max_size: u64,
+    _check: &dyn Fn(&Head) -> Result<()>,
+) -> Result<Vec<u8>> {
+    Err(download_error(
+        url,
+        "downloading is only supported on Windows",
+    ))
+}
+
+/// The content types of the images Windows shows on toasts.
+const CONTENT_TYPES: [&str; 3] = ["image/png", "image/jpeg", "image/gif"];
+
+/// The status and headers of a response, checked before its body is read.
+pub(crate) struct Head {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub content_length: Option<u64>,
+}
+
+/// Downloads `http` and `https` images into the [ImageCache] before a toast is shown.
+///
+/// Windows only loads remote images for packaged apps, so unpackaged apps get blank images instead.
+/// A [Notifier](crate::Notifier) set up [with_remote_images](crate::Notifier::with_remote_images)
+/// downloads them first, and shows the downloaded file. Images that cannot be downloaded are replaced
+/// by the [fallback](Self::fallback) image, or left to Windows if there is none.
+///
+/// Every URL is downloaded once per `RemoteImages`, and clones share what was downloaded.
+/// Downloads of different toasts run in parallel, so a URL requested again while it is
+/// still downloading may be downloaded twice.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration as StdDuration;
+/// use tauri_winrt_notification::{IconCrop, ImageSource, Notifier, RemoteImages, Toast};
+///
+/// let remote = RemoteImages::new()
+///     .timeout(StdDuration::from_secs(3))
+///     .fallback(ImageSource::bytes(include_bytes!("../../resources/test/chick.jpeg")));
+/// let notifier = Notifier::new(Toast::POWERSHELL_APP_ID)
+///     .expect("notifier unavailable")
+///     .with_remote_images(remote);
+///
+/// let avatar = ImageSource::uri("https://example.com/avatar.png").unwrap();
+/// let toast = Toast::new(Toast::POWERSHELL_APP_ID).icon(avatar, IconCrop::Circular, "avatar");
+/// notifier.show(&toast).expect("notification failed");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RemoteImages {
+    max_size: u64,
+    timeout: StdDuration,
+    fallback: Option<ImageSource>,
+    downloaded: Arc<Mutex<HashMap<String, PathBuf>>>,
+}
+
+impl RemoteImages {
+    /// Download images of up to 3 MB, giving up on each after 5 seconds, without a fallback.
+    pub fn new() -> RemoteImages {
+        RemoteImages {
+            max_size: 3 * 1024 * 1024,
+            timeout: StdDuration::from_secs(5),
+            fallback: None,
+            downloaded: Default::default(),
+        }
+    }
+
+    /// Set the largest image in bytes that is downloaded.
+    pub fn max_size(mut self, bytes: u64) -> RemoteImages {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Set how long downloading a single image may take.
+    ///
+    /// Showing a toast waits for its images, which are downloaded in parallel, so keep this short.
+    pub fn timeout(mut self, timeout: StdDuration) -> RemoteImages {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Show `source` instead of images that cannot be downloaded.
+    pub fn fallback(mut self, source: impl Into<ImageSource>) -> RemoteImages {
+        self.fallback = Some(source.into());
+        self
+    }
+
+    /// Download the image at `url`.
+    ///
+    /// Fails with [Error::Download] if the server does not answer in time, answers with an error,
+    /// or sends something other than a PNG, JPEG or GIF image of at most [max_size](Self::max_size) bytes.
+    /// Always fails on platforms other than Windows.
+    pub fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        let deadline = Instant::now() + self.timeout;
+        let body = get(url, deadline, self.max_size, &|head| self.check(url, head))?;
+        if body.len() as u64 > self.max_size {
+            return Err(self.too_large(url));
+        }
+        Ok(body)
+    }
+
+    /// The local source to show instead of a remote `source`.
+    pub(crate) fn resolve(&self, source: &ImageSource, cache: &ImageCache) -> ImageSource {
+        let Some(url) = source.as_remote_uri() else {
+            return source.clone();
+        };
+        let known = self.downloaded.lock().unwrap().get(url).cloned();
+        if let Some(path) = known.filter(|path| path.is_file()) {
+            return ImageSource::path(&path);
+        }
+        // not locked while downloading, so a slow server does not hold up other toasts
+        match self.fetch(url).and_then(|data| cache.store(&data)) {
+            Ok(path) => {
+                self.downloaded
+                    .lock()
+                    .unwrap()
+                    .insert(url.to_owned(), path.clone());
+                ImageSource::path(&path)
+            }
+            Err(_) => self.fallback.clone().unwrap_or_else(|| source.clone()),
+        }
+    }
+
+    fn check(&self, url: &str, head: &Head) -> Result<()> {
+        if !(200..300).contains(&head.status) {
+            return Err(download_error(
+                url,
+                format!("the server answered with status {}", head.status),
+            ));
+        }
+        let content_type = head.content_type.as_deref().unwrap_or_default();
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        if !CONTENT_TYPES
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(media_type))
+        {
+            return Err(download_error(
+                url,
+                format!("`{content_type}` is not a supported image type"),
+            ));
+        }
+        if head.content_length.is_some_and(|len| len > self.max_size) {
+            return Err(self.too_large(url));
+        }
+        Ok(())
+    }
+
+    fn too_large(&self, url: &str) -> Error {
+        download_error(
+            url,
+            format!("the image is larger than {} bytes", self.max_size),
+        )
+    }
+}
+
+impl Default for RemoteImages {
+    fn default() -> Self {
+        RemoteImages::new()
+    }
+}
+
+pub(crate) fn download_error(url: &str, reason: impl Into<String>) -> Error {
+    Error::Download {
+        url: url.to_owned(),
+        reason: reason.into(),
+    }
+}
+
+/// Fails with a timeout once `deadline` has passed, and returns the time left otherwise.
+#[cfg(any(windows, test))]
+pub(crate) fn remaining(url: &str, deadline: Instant) -> Result<StdDuration> {
+    deadline
+        .checked_duration_since(Instant::now())
+        .filter(|left| !left.is_zero())
+        .ok_or_else(|| download_error(url, "timed out"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\nremote image";
+
+    /// A local server answering every request according to its path, and counting them.
+    fn serve() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                counter.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let mut request = Vec::new();
+                    let mut buf = [0; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match stream.read(&mut buf) {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split(' ').nth(1).unwrap_or_default();
+                    let (status, content_type, body): (_, _, Vec<u8>) = match path {
+                        "/avatar.png" => ("200 OK", "image/png", PNG.to_vec()),
+                        "/page" => ("200 OK", "text/html; charset=utf-8", b"<html/>".to_vec()),
+                        "/large.png" => ("200 OK", "image/png", vec![0; 4096]),
+                        "/slow.png" => {
+                            std::thread::sleep(StdDuration::from_millis(500));
+                            ("200 OK", "image/png", PNG.to_vec())
+                        }
+                        _ => ("404 Not Found", "text/plain", b"not found".to_vec()),
+                    };
+                    let head = format!(
+                        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    );
+                    let _ = stream.write_all(head.as_bytes());
+                    let _ = stream.write_all(&body);
+                });
+            }
+        });
+        (base, requests)
+    }
+
+    fn cache(name: &str) -> ImageCache {
+        ImageCache::new(
+            std::env::temp_dir()
+                .join("tauri-winrt-notification-tests")
+                .join(format!("{name}-{}", std::process::id())),
+        )
+    }
+
+    fn reason(result: Result<Vec<u8>>) -> String {
+        match result {
+            Err(Error::Download { reason, .. }) => reason,
+            result => panic!("expected a download error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn fetches_images() {
+        let (base, _) = serve();
+        let remote = RemoteImages::new();
+        assert_eq!(remote.fetch(&format!("{base}/avatar.png")).unwrap(), PNG);
+    }
+
+    #[test]
+    fn rejects_other_content_types() {
+        let (base, _) = serve();
+        let remote = RemoteImages::new();
+        assert_eq!(
+            reason(remote.fetch(&format!("{base}/page"))),
+            "`text/html; charset=utf-8` is not a supported image type"
+        );
+    }
+
+    #[test]
+    fn rejects_large_images() {
+        let (base, _) = serve();
+        let remote = RemoteImages::new().max_size(1024);
+        assert_eq!(
+            reason(remote.fetch(&format!("{base}/large.png"))),
+            "the image is larger than 1024 bytes"
+        );
+        let remote = remote.max_size(4096);
+        assert_eq!(
+            remote.fetch(&format!("{base}/large.png")).unwrap().len(),
+            4096
+        );
+    }
+
+    #[test]
+    fn times_out() {
+        let (base, _) = serve();
+        let remote = RemoteImages::new().timeout(StdDuration::from_millis(100));
+        assert_eq!(
+            reason(remote.fetch(&format!("{base}/slow.png"))),
+            "timed out"
+        );
+    }
+
+    #[test]
+    fn rejects_error_statuses() {
+        let (base, _) = serve();
+        let remote = RemoteImages::new();
+        assert_eq!(
+            reason(remote.fetch(&format!("{base}/missing.png"))),
+            "the server answered with status 404"
+        );
+    }
+
+    #[test]
+    fn resolves_into_the_cache_once() {
+        let (base, requests) = serve();
+        let cache = cache("remote-resolve");
+        let remote = RemoteImages::new();
+        let source = ImageSource::uri(&format!("{base}/avatar.png")).unwrap();
+
+        let resolved = remote.resolve(&source, &cache);
+        assert_eq!(resolved.as_path(), Some(cache.path_of(PNG).as_path()));
+        assert_eq!(remote.clone().resolve(&source, &cache), resolved);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let local = ImageSource::uri("ms-appx:///avatar.png").unwrap();
+        assert_eq!(remote.resolve(&local, &cache), local);
+
+        std::fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn downloads_in_parallel() {
+        let (base, requests) = serve();
+        let cache = cache("remote-parallel");
+        let remote = RemoteImages::new();
+
+        let slow = ImageSource::uri(&format!("{base}/slow.png")).unwrap();
+        let (slow_remote, slow_cache) = (remote.clone(), cache.clone());
+        let slow = std::thread::spawn(move || slow_remote.resolve(&slow, &slow_cache));
+        while requests.load(Ordering::SeqCst) == 0 {
+            std::thread::sleep(StdDuration::from_millis(1));
+        }
+
+        let started = Instant::now();
+        let avatar = ImageSource::uri(&format!("{base}/avatar.png")).unwrap();
+        assert!(remote.resolve(&avatar, &cache).as_path().is_some());
+        assert!(started.elapsed() < StdDuration::from_millis(400));
+        assert!(slow.join().unwrap().as_path().is_some());
+
+        std::fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn falls_back() {
+        let (base, _) = serve();
+        let cache = cache("remote-fallback");
+        let source = ImageSource::uri(&format!("{base}/page")).unwrap();
+        assert_eq!(RemoteImages::new().resolve(&source, &cache), source);
+
+        let fallback = ImageSource::uri("ms-appx:///fallback.png").unwrap();
+        let remote = RemoteImages::new().fallback(fallback.clone());
+        assert_eq!(remote.resolve(&source, &cache), fallback);
+        assert!(!cache.dir().exists());
+    }
+}
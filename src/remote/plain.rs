@@ -0,0 +1,160 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A minimal HTTP/1.0 client standing in for `Windows.Web.Http` in the tests on other platforms,
+//! where the library itself never downloads anything.
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Instant;
+
+use super::{download_error, remaining, Head};
+use crate::Result;
+
+/// The largest status line and headers accepted.
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+/// Download `url`, reading at most `max_size + 1` bytes of the body once `check` accepted the head.
+///
+/// Only plain `http` is supported.
+pub(crate) fn get(
+    url: &str,
+    deadline: Instant,
+    max_size: u64,
+    check: &dyn Fn(&Head) -> Result<()>,
+) -> Result<Vec<u8>> {
+    let rest = url
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("http://"))
+        .map(|_| &url[7..])
+        .ok_or_else(|| download_error(url, "only `http` is supported on this platform"))?;
+    let (authority, path) = match rest.find(['/', '?', '#']) {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let path = path.split('#').next().unwrap_or_default();
+    let path = if path.starts_with('/') {
+        path.to_owned()
+    } else {
+        format!("/{path}")
+    };
+    let address = if authority.ends_with(']') || !authority.contains(':') {
+        format!("{authority}:80")
+    } else {
+        authority.to_owned()
+    };
+
+    let io = |error: std::io::Error| match error.kind() {
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => download_error(url, "timed out"),
+        _ => download_error(url, error.to_string()),
+    };
+    let address = address
+        .to_socket_addrs()
+        .map_err(io)?
+        .next()
+        .ok_or_else(|| download_error(url, "the host has no address"))?;
+    let mut stream = TcpStream::connect_timeout(&address, remaining(url, deadline)?).map_err(io)?;
+    stream
+        .set_write_timeout(Some(remaining(url, deadline)?))
+        .map_err(io)?;
+    let request = format!(
+        "GET {path} HTTP/1.0\r\nHost: {authority}\r\nAccept: image/*\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).map_err(io)?;
+
+    let mut read = |buf: &mut [u8]| -> Result<usize> {
+        stream
+            .set_read_timeout(Some(remaining(url, deadline)?))
+            .map_err(io)?;
+        stream.read(buf).map_err(io)
+    };
+
+    let mut data = Vec::new();
+    let mut buf = [0; 8 * 1024];
+    let head_len = loop {
+        if let Some(i) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+        if data.len() > MAX_HEAD_SIZE {
+            return Err(download_error(url, "the response headers are too large"));
+        }
+        match read(&mut buf)? {
+            0 => return Err(download_error(url, "the connection closed early")),
+            n => data.extend_from_slice(&buf[..n]),
+        }
+    };
+    check(&parse_head(url, &data[..head_len])?)?;
+
+    let limit = usize::try_from(max_size.saturating_add(1)).unwrap_or(usize::MAX);
+    let mut body = data.split_off(head_len);
+    while body.len() < limit {
+        match read(&mut buf)? {
+            0 => break,
+            n => body.extend_from_slice(&buf[..n]),
+        }
+    }
+    body.truncate(limit);
+    Ok(body)
+}
+
+fn parse_head(url: &str, head: &[u8]) -> Result<Head> {
+    let malformed = || download_error(url, "malformed response");
+    let head = std::str::from_utf8(head).map_err(|_| malformed())?;
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(malformed)?;
+
+    let mut parsed = Head {
+        status,
+        content_type: None,
+        content_length: None,
+    };
+    for (name, value) in lines.filter_map(|line| line.split_once(':')) {
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-type") {
+            parsed.content_type = Some(value.to_owned());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            parsed.content_length = Some(value.parse().map_err(|_| malformed())?);
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn parses_heads() {
+        let head = parse_head(
+            "http://localhost/",
+            b"HTTP/1.1 200 OK\r\ncontent-type: image/png\r\nContent-Length: 12\r\nX-Other: a:b\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(head.status, 200);
+        assert_eq!(head.content_type.as_deref(), Some("image/png"));
+        assert_eq!(head.content_length, Some(12));
+
+        assert!(matches!(
+            parse_head("http://localhost/", b"garbage\r\n\r\n"),
+            Err(Error::Download { .. })
+        ));
+    }
+
+    #[test]
+    fn only_supports_http() {
+        let deadline = Instant::now() + std::time::Duration::from_secs(1);
+        match get("https://localhost/image.png", deadline, 1, &|_| Ok(())) {
+            Err(Error::Download { reason, .. }) => {
+                assert_eq!(reason, "only `http` is supported on this platform")
+            }
+            result => panic!("expected a download error, got {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Downloading with `Windows.Web.Http`, which brings `https` and the proxy settings of the system.
+
+use std::time::{Duration as StdDuration, Instant};
+
+use windows::core::{RuntimeType, HSTRING};
+use windows::Foundation::{AsyncStatus, IAsyncOperationWithProgress, Uri};
+use windows::Storage::Streams::{Buffer, DataReader, InputStreamOptions};
+use windows::Web::Http::{HttpClient, HttpCompletionOption};
+
+use super::{download_error, remaining, Head};
+use crate::Result;
+
+/// How often a running download is checked for its deadline.
+const POLL_INTERVAL: StdDuration = StdDuration::from_millis(5);
+/// The most bytes of the body read at once.
+const CHUNK_SIZE: u32 = 64 * 1024;
+
+/// Download `url`, returning at most `max_size + 1` bytes of the body once `check` accepted the head.
+pub(crate) fn get(
+    url: &str,
+    deadline: Instant,
+    max_size: u64,
+    check: &dyn Fn(&Head) -> Result<()>,
+) -> Result<Vec<u8>> {
+    let os = |error: windows::core::Error| download_error(url, error.message());
+
+    let uri = Uri::CreateUri(&HSTRING::from(url)).map_err(os)?;
+    let client = HttpClient::new().map_err(os)?;
+    let response = client
+        .GetWithOptionAsync(&uri, HttpCompletionOption::ResponseHeadersRead)
+        .map_err(os)?;
+    let response = wait(url, response, deadline)?;
+
+    let content = response.Content().map_err(os)?;
+    let headers = content.Headers().map_err(os)?;
+    check(&Head {
+        status: u16::try_from(response.StatusCode().map_err(os)?.0).unwrap_or_default(),
+        content_type: headers
+            .ContentType()
+            .and_then(|content_type| content_type.MediaType())
+            .ok()
+            .map(|media_type| media_type.to_string_lossy()),
+        content_length: headers
+            .ContentLength()
+            .and_then(|length| length.Value())
+            .ok(),
+    })?;
+
+    // read in chunks, so a body without or with a wrong Content-Length is never buffered whole
+    let stream = wait(url, content.ReadAsInputStreamAsync().map_err(os)?, deadline)?;
+    let buffer = Buffer::Create(CHUNK_SIZE).map_err(os)?;
+    let limit = usize::try_from(max_size.saturating_add(1)).unwrap_or(usize::MAX);
+    let mut body = Vec::new();
+    while body.len() < limit {
+        let count =
+            u32::try_from(limit - body.len()).map_or(CHUNK_SIZE, |left| left.min(CHUNK_SIZE));
+        let read = stream
+            .ReadAsync(&buffer, count, InputStreamOptions::Partial)
+            .map_err(os)?;
+        let chunk = wait(url, read, deadline)?;
+        let len = chunk.Length().map_err(os)? as usize;
+        if len == 0 {
+            break;
+        }
+        let start = body.len();
+        body.resize(start + len, 0);
+        DataReader::FromBuffer(&chunk)
+            .and_then(|reader| reader.ReadBytes(&mut body[start..]))
+            .map_err(os)?;
+    }
+    // stop downloading the rest of a body that is too large
+    let _ = stream.Close();
+    let _ = response.Close();
+    Ok(body)
+}
+
+/// Block until `operation` completes, cancelling it once `deadline` has passed.
+fn wait<T: RuntimeType + 'static, P: RuntimeType + 'static>(
+    url: &str,
+    operation: IAsyncOperationWithProgress<T, P>,
+    deadline: Instant,
+) -> Result<T> {
+    let os = |error: windows::core::Error| download_error(url, error.message());
+    while operation.Status().map_err(os)? == AsyncStatus::Started {
+        if let Err(timed_out) = remaining(url, deadline) {
+            let _ = operation.Cancel();
+            return Err(timed_out);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    operation.GetResults().map_err(os)
+}
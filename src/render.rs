@@ -0,0 +1,926 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Turns a [Toast] into its xml payload.
+//!
+//! Every decision about what a [TargetProfile] can show is made here.
+
+use std::fmt::{Display, Write};
+
+use quick_xml::escape::escape;
+
+use crate::{
+    Capability, Duration, IconCrop, Image, ImageDefaults, ImagePlacement, LegacyTemplate, Progress,
+    Scenario, Sound, TargetProfile, Toast,
+};
+
+/// The base URI of relative image sources if none is set.
+const DEFAULT_BASE_URI: &str = "ms-appx:///";
+
+/// A toast rendered for a [TargetProfile], see [Toast::render].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendered {
+    /// The xml payload.
+    pub xml: String,
+    /// What had to be changed or left out because the profile does not support it.
+    pub downgrades: Vec<Downgrade>,
+}
+
+/// A part of a toast that was changed or left out because the [TargetProfile] does not support it.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Downgrade {
+    /// A legacy template was used instead of `ToastGeneric`.
+    LegacyTemplate { template: LegacyTemplate },
+    /// The [icon](Toast::icon) was shown as a regular image.
+    IconAsImage,
+    /// The [hero](Toast::hero) image was shown as a regular image.
+    HeroAsImage,
+    /// The icon was not cropped to a circle.
+    CircleCropDropped,
+    /// Images were left out because the legacy templates show at most one image.
+    ImagesDropped { count: usize },
+    /// Texts were left out because the legacy template has fewer lines.
+    TextsDropped { count: usize },
+    /// The [progress](Toast::progress) bar was left out.
+    ProgressDropped,
+    /// The buttons and inputs were left out.
+    ActionsDropped { count: usize },
+    /// The [header](Toast::header) was left out.
+    HeaderDropped,
+    /// The [fallback](crate::AudioSource::fallback) of a custom sound was played instead.
+    CustomAudioReplaced,
+    /// The texts of an incoming call were not centered.
+    CallTextsNotCentered,
+    /// The [urgent](Scenario::Urgent) scenario was left out.
+    UrgentScenarioDropped,
+    /// Buttons were shown without their [style](crate::ButtonStyle).
+    ButtonStylesDropped,
+    /// The [activation type](Toast::activation_type) was left out.
+    ActivationTypeDropped,
+    /// URIs were opened with the default app for their scheme instead of their
+    /// [target application](crate::ProtocolUri::target_application).
+    ProtocolTargetDropped,
+}
+
+impl Downgrade {
+    /// The capability the profile is missing.
+    pub fn capability(&self) -> Capability {
+        match self {
+            Downgrade::LegacyTemplate { .. }
+            | Downgrade::ImagesDropped { .. }
+            | Downgrade::TextsDropped { .. } => Capability::AdaptiveTemplate,
+            Downgrade::IconAsImage => Capability::AppLogoOverride,
+            Downgrade::HeroAsImage => Capability::Hero,
+            Downgrade::CircleCropDropped => Capability::CircleCrop,
+            Downgrade::ProgressDropped => Capability::Progress,
+            Downgrade::ActionsDropped { .. } | Downgrade::ActivationTypeDropped => {
+                Capability::Actions
+            }
+            Downgrade::HeaderDropped => Capability::Header,
+            Downgrade::CustomAudioReplaced => Capability::CustomAudio,
+            Downgrade::CallTextsNotCentered => Capability::CallLayout,
+            Downgrade::UrgentScenarioDropped => Capability::UrgentScenario,
+            Downgrade::ButtonStylesDropped => Capability::ButtonStyle,
+            Downgrade::ProtocolTargetDropped => Capability::ProtocolTarget,
+        }
+    }
+}
+
+impl Display for Downgrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Downgrade::LegacyTemplate { template } => {
+                write!(f, "used the legacy {template} template")
+            }
+            Downgrade::IconAsImage => write!(f, "showed the icon as a regular image"),
+            Downgrade::HeroAsImage => write!(f, "showed the hero image as a regular image"),
+            Downgrade::CircleCropDropped => write!(f, "did not crop the icon to a circle"),
+            Downgrade::ImagesDropped { count } => write!(f, "left out {count} images"),
+            Downgrade::TextsDropped { count } => write!(f, "left out {count} texts"),
+            Downgrade::ProgressDropped => write!(f, "left out the progress bar"),
+            Downgrade::ActionsDropped { count } => {
+                write!(f, "left out {count} buttons and inputs")
+            }
+            Downgrade::HeaderDropped => write!(f, "left out the header"),
+            Downgrade::CustomAudioReplaced => {
+                write!(f, "played the fallback of the custom sound")
+            }
+            Downgrade::CallTextsNotCentered => {
+                write!(f, "did not center the texts of the incoming call")
+            }
+            Downgrade::UrgentScenarioDropped => write!(f, "left out the urgent scenario"),
+            Downgrade::ButtonStylesDropped => write!(f, "left out the button styles"),
+            Downgrade::ActivationTypeDropped => write!(f, "left out the activation type"),
+            Downgrade::ProtocolTargetDropped => {
+                write!(f, "left out the target applications of the URIs")
+            }
+        }
+    }
+}
+
+pub(crate) fn render(toast: &Toast, profile: TargetProfile) -> Rendered {
+    let mut downgrades = Vec::new();
+    let mut xml = String::from("<toast");
+    let audio = toast.audio();
+
+    match audio.duration {
+        Some(Duration::Long) => xml.push_str(r#" duration="long""#),
+        Some(Duration::Short) => xml.push_str(r#" duration="short""#),
+        None => {}
+    }
+
+    match toast.scenario {
+        Scenario::Default => {}
+        Scenario::Alarm => xml.push_str(r#" scenario="alarm""#),
+        Scenario::Reminder => xml.push_str(r#" scenario="reminder""#),
+        Scenario::IncomingCall => xml.push_str(r#" scenario="incomingCall""#),
+        Scenario::Urgent if profile.supports(Capability::UrgentScenario) => {
+            xml.push_str(r#" scenario="urgent""#)
+        }
+        Scenario::Urgent => downgrades.push(Downgrade::UrgentScenarioDropped),
+    }
+
+    if let Some(launch) = &toast.launch {
+        let _ = write!(xml, r#" launch="{}""#, escape(launch));
+    }
+    match toast.activation_type {
+        Some(activation_type) if profile.supports(Capability::Actions) => {
+            let _ = write!(xml, r#" activationType="{activation_type}""#);
+        }
+        Some(_) => downgrades.push(Downgrade::ActivationTypeDropped),
+        None => {}
+    }
+    let protocol_target = match &toast.launch_target {
+        Some(pfn) if profile.supports(Capability::ProtocolTarget) => Some(pfn),
+        Some(_) if profile.supports(Capability::Actions) => {
+            downgrades.push(Downgrade::ProtocolTargetDropped);
+            None
+        }
+        _ => None,
+    };
+    if let Some(pfn) = protocol_target {
+        let _ = write!(
+            xml,
+            r#" protocolActivationTargetApplicationPfn="{}""#,
+            escape(pfn)
+        );
+    }
+
+    let styled =
+        toast.buttons.iter().any(|b| b.style.is_some()) && profile.supports(Capability::Actions);
+    let styled = match styled {
+        true if !profile.supports(Capability::ButtonStyle) => {
+            downgrades.push(Downgrade::ButtonStylesDropped);
+            false
+        }
+        styled => styled,
+    };
+    if styled {
+        xml.push_str(r#" useButtonStyle="true""#);
+    }
+
+    xml.push_str("><visual");
+    let inherited = Inherited {
+        add_image_query: false,
+        base_uri: DEFAULT_BASE_URI,
+    }
+    .with(&toast.visual_images, &mut xml);
+    xml.push('>');
+    if profile.supports(Capability::AdaptiveTemplate) {
+        adaptive_binding(toast, profile, inherited, &mut xml, &mut downgrades);
+    } else {
+        legacy_binding(toast, inherited, &mut xml, &mut downgrades);
+    }
+    xml.push_str("</visual>");
+
+    match &audio.sound {
+        Some(Sound::Custom(source)) if !profile.supports(Capability::CustomAudio) => {
+            downgrades.push(Downgrade::CustomAudioReplaced);
+            audio_xml(&mut xml, Some(&source.fallback_sound()));
+        }
+        sound => audio_xml(&mut xml, sound.as_ref()),
+    }
+
+    let action_count = toast.inputs.len() + toast.buttons.len();
+    if action_count > 0 && !profile.supports(Capability::Actions) {
+        downgrades.push(Downgrade::ActionsDropped {
+            count: action_count,
+        });
+    } else if action_count > 0 {
+        xml.push_str("<actions>");
+        for input in &toast.inputs {
+            xml.push_str(&input.xml());
+        }
+        let mut targets_dropped = false;
+        for b in &toast.buttons {
+            let _ = write!(
+                xml,
+                "<action content='{}' arguments='{}'",
+                escape(&b.content),
+                escape(&b.action)
+            );
+            if let Some(target) = &b.protocol {
+                xml.push_str(r#" activationType="protocol""#);
+                match target {
+                    Some(pfn) if profile.supports(Capability::ProtocolTarget) => {
+                        let _ = write!(
+                            xml,
+                            r#" protocolActivationTargetApplicationPfn="{}""#,
+                            escape(pfn)
+                        );
+                    }
+                    Some(_) => targets_dropped = true,
+                    None => {}
+                }
+            }
+            if let Some(style) = b.style.filter(|_| styled) {
+                let _ = write!(xml, r#" hint-buttonStyle="{style}""#);
+            }
+            xml.push_str("/>");
+        }
+        if targets_dropped && !downgrades.contains(&Downgrade::ProtocolTargetDropped) {
+            downgrades.push(Downgrade::ProtocolTargetDropped);
+        }
+        xml.push_str("</actions>");
+    }
+
+    match &toast.header {
+        Some(header) if profile.supports(Capability::Header) => {
+            let _ = write!(
+                xml,
+                r#"<header id="{}" title="{}" arguments="{}"/>"#,
+                escape(&header.id),
+                escape(&header.title),
+                escape(&header.arguments)
+            );
+        }
+        Some(_) => downgrades.push(Downgrade::HeaderDropped),
+        None => {}
+    }
+
+    xml.push_str("</toast>");
+    Rendered { xml, downgrades }
+}
+
+fn audio_xml(xml: &mut String, sound: Option<&Sound>) {
+    match sound {
+        None => xml.push_str(r#"<audio silent="true"/>"#),
+        Some(Sound::Default) => {}
+        Some(Sound::Loop(sound)) => {
+            let _ = write!(
+                xml,
+                r#"<audio loop="true" src="ms-winsoundevent:Notification.Looping.{}"/>"#,
+                sound
+            );
+        }
+        Some(Sound::Single(sound)) => {
+            let _ = write!(
+                xml,
+                r#"<audio src="ms-winsoundevent:Notification.Looping.{}"/>"#,
+                sound
+            );
+        }
+        Some(Sound::Custom(source)) => {
+            let _ = write!(xml, r#"<audio src="{}"/>"#, escape(source.as_uri()));
+        }
+        Some(sound) => {
+            let _ = write!(
+                xml,
+                r#"<audio src="ms-winsoundevent:Notification.{}"/>"#,
+                sound
+            );
+        }
+    }
+}
+
+/// The image attributes an element inherits from the elements above it.
+#[derive(Clone, Copy)]
+struct Inherited<'a> {
+    add_image_query: bool,
+    base_uri: &'a str,
+}
+
+impl<'a> Inherited<'a> {
+    /// Write the attributes of `defaults` that change what is inherited, and return what the elements below inherit.
+    fn with(self, defaults: &'a ImageDefaults, xml: &mut String) -> Inherited<'a> {
+        let mut inherited = self;
+        if let Some(add_image_query) = defaults.add_image_query {
+            if add_image_query != self.add_image_query {
+                let _ = write!(xml, r#" addImageQuery="{add_image_query}""#);
+            }
+            inherited.add_image_query = add_image_query;
+        }
+        if let Some(base_uri) = defaults.base_uri.as_deref() {
+            if base_uri != self.base_uri {
+                let _ = write!(xml, r#" baseUri="{}""#, escape(base_uri));
+            }
+            inherited.base_uri = base_uri;
+        }
+        inherited
+    }
+}
+
+fn adaptive_binding(
+    toast: &Toast,
+    profile: TargetProfile,
+    inherited: Inherited,
+    xml: &mut String,
+    downgrades: &mut Vec<Downgrade>,
+) {
+    xml.push_str(r#"<binding template="ToastGeneric""#);
+    let inherited = inherited.with(&toast.binding_images, xml);
+    xml.push('>');
+
+    for image in &toast.images {
+        match image.placement {
+            ImagePlacement::AppLogo(crop) if profile.supports(Capability::AppLogoOverride) => {
+                let crop = match crop {
+                    IconCrop::Circular if profile.supports(Capability::CircleCrop) => {
+                        r#" hint-crop="circle""#
+                    }
+                    IconCrop::Circular => {
+                        downgrades.push(Downgrade::CircleCropDropped);
+                        ""
+                    }
+                    IconCrop::Square => "",
+                };
+                let placement = format!(r#"placement="appLogoOverride"{crop}"#);
+                image_xml(xml, image, &placement, inherited, true);
+            }
+            ImagePlacement::AppLogo(_) => {
+                downgrades.push(Downgrade::IconAsImage);
+                image_xml(xml, image, INLINE_IMAGE, inherited, true);
+            }
+            ImagePlacement::Hero if profile.supports(Capability::Hero) => {
+                image_xml(xml, image, r#"placement="Hero""#, inherited, true);
+            }
+            ImagePlacement::Hero => {
+                downgrades.push(Downgrade::HeroAsImage);
+                image_xml(xml, image, INLINE_IMAGE, inherited, true);
+            }
+            ImagePlacement::Inline if image.options.circle_crop => {
+                let placement = if profile.supports(Capability::CircleCrop) {
+                    r#"id="1" hint-crop="circle""#
+                } else {
+                    downgrades.push(Downgrade::CircleCropDropped);
+                    INLINE_IMAGE
+                };
+                image_xml(xml, image, placement, inherited, true);
+            }
+            ImagePlacement::Inline => image_xml(xml, image, INLINE_IMAGE, inherited, true),
+        }
+    }
+
+    let centered = match toast.center_call_texts && toast.scenario == Scenario::IncomingCall {
+        true if !profile.supports(Capability::CallLayout) => {
+            downgrades.push(Downgrade::CallTextsNotCentered);
+            false
+        }
+        centered => centered,
+    };
+    for (id, text) in [(1, &toast.title), (2, &toast.line1), (3, &toast.line2)] {
+        if let Some(text) = text {
+            text_xml(xml, id, text, centered);
+        }
+    }
+
+    match toast.progress {
+        Some(_) if profile.supports(Capability::Progress) => xml.push_str(Progress::xml()),
+        Some(_) => downgrades.push(Downgrade::ProgressDropped),
+        None => {}
+    }
+
+    xml.push_str("</binding>");
+}
+
+fn legacy_binding(
+    toast: &Toast,
+    inherited: Inherited,
+    xml: &mut String,
+    downgrades: &mut Vec<Downgrade>,
+) {
+    let texts: Vec<&String> = [&toast.title, &toast.line1, &toast.line2]
+        .into_iter()
+        .flatten()
+        .collect();
+    let template = toast
+        .legacy_template
+        .unwrap_or_else(|| LegacyTemplate::fitting(texts.len(), !toast.images.is_empty()));
+    downgrades.push(Downgrade::LegacyTemplate { template });
+
+    let _ = write!(xml, r#"<binding template="{}""#, template);
+    let inherited = inherited.with(&toast.binding_images, xml);
+    xml.push('>');
+
+    // the legacy templates show nothing if there is more than 1 image
+    let shown_images = if template.has_image() { 1 } else { 0 };
+    let dropped_images = toast.images.len().saturating_sub(shown_images);
+    if dropped_images > 0 {
+        downgrades.push(Downgrade::ImagesDropped {
+            count: dropped_images,
+        });
+    }
+    if let Some(image) = toast.images.last().filter(|_| template.has_image()) {
+        match image.placement {
+            ImagePlacement::AppLogo(_) => downgrades.push(Downgrade::IconAsImage),
+            ImagePlacement::Hero => downgrades.push(Downgrade::HeroAsImage),
+            ImagePlacement::Inline => {}
+        }
+        image_xml(xml, image, INLINE_IMAGE, inherited, false);
+    }
+
+    for (id, text) in (1..).zip(texts.iter().take(template.text_count())) {
+        text_xml(xml, id, text, false);
+    }
+    if texts.len() > template.text_count() {
+        downgrades.push(Downgrade::TextsDropped {
+            count: texts.len() - template.text_count(),
+        });
+    }
+
+    if toast.progress.is_some() {
+        downgrades.push(Downgrade::ProgressDropped);
+    }
+
+    xml.push_str("</binding>");
+}
+
+const INLINE_IMAGE: &str = r#"id="1""#;
+
+/// Write `image`, with the `hint-` attributes only if `adaptive`.
+fn image_xml(
+    xml: &mut String,
+    image: &Image,
+    placement: &str,
+    inherited: Inherited,
+    adaptive: bool,
+) {
+    let _ = write!(
+        xml,
+        r#"<image {} src="{}" alt="{}""#,
+        placement,
+        escape(&image.source.to_uri()),
+        escape(&image.alt_text)
+    );
+    let options = &image.options;
+    if let Some(add_image_query) = options.add_image_query {
+        if add_image_query != inherited.add_image_query {
+            let _ = write!(xml, r#" addImageQuery="{add_image_query}""#);
+        }
+    }
+    if adaptive && options.remove_margin {
+        xml.push_str(r#" hint-removeMargin="true""#);
+    }
+    if let Some(align) = options.align.filter(|_| adaptive) {
+        let _ = write!(xml, r#" hint-align="{align}""#);
+    }
+    xml.push_str("/>");
+}
+
+/// Write `text`, centered as on incoming calls if `centered`.
+fn text_xml(xml: &mut String, id: usize, text: &str, centered: bool) {
+    let hint = if centered {
+        r#" hint-callScenarioCenterAlign="true""#
+    } else {
+        ""
+    };
+    let _ = write!(xml, r#"<text id="{}"{}>{}</text>"#, id, hint, escape(text));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Header, ImageAlign, ImageOptions, ImageSource};
+
+    fn source(name: &str) -> ImageSource {
+        format!("ms-appx:///{name}").parse().unwrap()
+    }
+
+    fn toast() -> Toast {
+        Toast::new(Toast::POWERSHELL_APP_ID)
+            .title("title")
+            .hero(source("hero.png"), "hero")
+            .icon(source("icon.png"), IconCrop::Circular, "icon")
+            .add_button("Ok", "ok")
+    }
+
+    #[test]
+    fn windows_11() {
+        let Rendered { xml, downgrades } = toast().render(TargetProfile::WINDOWS_11);
+        assert_eq!(downgrades, []);
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<toast><visual><binding template="ToastGeneric">"#,
+                r#"<image placement="Hero" src="ms-appx:///hero.png" alt="hero"/>"#,
+                r#"<image placement="appLogoOverride" hint-crop="circle" src="ms-appx:///icon.png" alt="icon"/>"#,
+                r#"<text id="1">title</text>"#,
+                r#"</binding></visual>"#,
+                r#"<actions><action content='Ok' arguments='ok'/></actions>"#,
+                r#"</toast>"#
+            )
+        );
+    }
+
+    #[test]
+    fn before_anniversary_update() {
+        let Rendered { xml, downgrades } = toast().render(TargetProfile::WINDOWS_10);
+        assert!(xml.contains(r#"<image id="1" src="ms-appx:///hero.png" alt="hero"/>"#));
+        assert!(xml.contains(
+            r#"<image placement="appLogoOverride" src="ms-appx:///icon.png" alt="icon"/>"#
+        ));
+        assert_eq!(
+            downgrades,
+            [Downgrade::HeroAsImage, Downgrade::CircleCropDropped]
+        );
+    }
+
+    #[test]
+    fn windows_8_1() {
+        let Rendered { xml, downgrades } = toast().render(TargetProfile::WINDOWS_8_1);
+        assert_eq!(
+            downgrades,
+            [
+                Downgrade::LegacyTemplate {
+                    template: LegacyTemplate::ToastImageAndText01
+                },
+                Downgrade::ImagesDropped { count: 1 },
+                Downgrade::IconAsImage,
+                Downgrade::ActionsDropped { count: 1 },
+            ]
+        );
+        // only the last image
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<toast><visual><binding template="ToastImageAndText01">"#,
+                r#"<image id="1" src="ms-appx:///icon.png" alt="icon"/>"#,
+                r#"<text id="1">title</text>"#,
+                r#"</binding></visual></toast>"#
+            )
+        );
+    }
+
+    fn legacy_toast(texts: usize, image: bool) -> Toast {
+        let mut toast = Toast::new(Toast::POWERSHELL_APP_ID);
+        if image {
+            toast = toast.image(source("image.png"), "image");
+        }
+        for (i, line) in ["title", "line 1", "line 2"]
+            .into_iter()
+            .take(texts)
+            .enumerate()
+        {
+            toast = match i {
+                0 => toast.title(line),
+                1 => toast.text1(line),
+                _ => toast.text2(line),
+            };
+        }
+        toast
+    }
+
+    fn legacy_binding(template: &str, image: bool, texts: &[&str]) -> String {
+        let mut xml = format!(r#"<toast><visual><binding template="{template}">"#);
+        if image {
+            xml.push_str(r#"<image id="1" src="ms-appx:///image.png" alt="image"/>"#);
+        }
+        for (id, text) in (1..).zip(texts) {
+            xml.push_str(&format!(r#"<text id="{id}">{text}</text>"#));
+        }
+        xml.push_str("</binding></visual></toast>");
+        xml
+    }
+
+    #[test]
+    fn legacy_templates_are_picked_automatically() {
+        let cases = [
+            (0, false, LegacyTemplate::ToastText01),
+            (1, false, LegacyTemplate::ToastText01),
+            (2, false, LegacyTemplate::ToastText02),
+            (3, false, LegacyTemplate::ToastText04),
+            (1, true, LegacyTemplate::ToastImageAndText01),
+            (2, true, LegacyTemplate::ToastImageAndText02),
+            (3, true, LegacyTemplate::ToastImageAndText04),
+        ];
+        let texts = ["title", "line 1", "line 2"];
+
+        for (count, image, template) in cases {
+            let Rendered { xml, downgrades } =
+                legacy_toast(count, image).render(TargetProfile::WINDOWS_8_1);
+            assert_eq!(downgrades, [Downgrade::LegacyTemplate { template }]);
+            assert_eq!(xml, legacy_binding(template.name(), image, &texts[..count]));
+        }
+    }
+
+    #[test]
+    fn legacy_templates_snapshots() {
+        let texts = ["title", "line 1", "line 2"];
+
+        for template in [
+            LegacyTemplate::ToastImageAndText01,
+            LegacyTemplate::ToastImageAndText02,
+            LegacyTemplate::ToastImageAndText03,
+            LegacyTemplate::ToastImageAndText04,
+        ] {
+            let xml = legacy_toast(3, true)
+                .legacy_template(template)
+                .to_xml(TargetProfile::WINDOWS_8_1);
+            assert_eq!(
+                xml,
+                legacy_binding(template.name(), true, &texts[..template.text_count()])
+            );
+        }
+
+        for template in [
+            LegacyTemplate::ToastText01,
+            LegacyTemplate::ToastText02,
+            LegacyTemplate::ToastText03,
+            LegacyTemplate::ToastText04,
+        ] {
+            let Rendered { xml, downgrades } = legacy_toast(3, false)
+                .legacy_template(template)
+                .render(TargetProfile::WINDOWS_8_1);
+            assert_eq!(
+                xml,
+                legacy_binding(template.name(), false, &texts[..template.text_count()])
+            );
+            if template.text_count() < 3 {
+                assert_eq!(
+                    downgrades[1],
+                    Downgrade::TextsDropped {
+                        count: 3 - template.text_count()
+                    }
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn explicit_text_template_drops_images() {
+        let Rendered { xml, downgrades } = legacy_toast(2, true)
+            .legacy_template(LegacyTemplate::ToastText03)
+            .render(TargetProfile::WINDOWS_8_1);
+        assert_eq!(
+            xml,
+            legacy_binding("ToastText03", false, &["title", "line 1"])
+        );
+        assert_eq!(
+            downgrades,
+            [
+                Downgrade::LegacyTemplate {
+                    template: LegacyTemplate::ToastText03
+                },
+                Downgrade::ImagesDropped { count: 1 }
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_template_is_ignored_on_windows_10() {
+        let xml = legacy_toast(1, false)
+            .legacy_template(LegacyTemplate::ToastText02)
+            .to_xml(TargetProfile::WINDOWS_10);
+        assert!(xml.contains(r#"<binding template="ToastGeneric">"#));
+    }
+
+    #[test]
+    fn progress_needs_anniversary_update() {
+        let progress = Progress {
+            tag: "tag".to_owned(),
+            title: "title".to_owned(),
+            status: "status".to_owned(),
+            value: 0.0,
+            value_string: String::new(),
+        };
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).progress(&progress);
+        assert!(toast
+            .to_xml(TargetProfile::ANNIVERSARY_UPDATE)
+            .contains("<progress"));
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_10);
+        assert!(!xml.contains("<progress"));
+        assert_eq!(downgrades, [Downgrade::ProgressDropped]);
+        assert_eq!(downgrades[0].capability(), Capability::Progress);
+    }
+
+    #[test]
+    fn header_needs_creators_update() {
+        let header = Header {
+            id: "chat".to_owned(),
+            title: "Chat & more".to_owned(),
+            arguments: "open=chat".to_owned(),
+        };
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID).header(&header);
+        assert!(toast
+            .to_xml(TargetProfile::CREATORS_UPDATE)
+            .contains(r#"<header id="chat" title="Chat &amp; more" arguments="open=chat"/>"#));
+
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::ANNIVERSARY_UPDATE);
+        assert!(!xml.contains("<header"));
+        assert_eq!(downgrades, [Downgrade::HeaderDropped]);
+    }
+
+    #[test]
+    fn image_attributes_are_inherited() {
+        let toast = Toast::new(Toast::POWERSHELL_APP_ID)
+            .image_defaults(ImageDefaults {
+                add_image_query: Some(true),
+                base_uri: Some("https://cdn.example.com/".to_owned()),
+            })
+            .binding_image_defaults(ImageDefaults {
+                // the same as the visual, so not repeated
+                add_image_query: Some(true),
+                base_uri: Some("https://cdn.example.com/avatars/".to_owned()),
+            })
+            .image(ImageSource::relative("alice.png"), "alice")
+            .image_with(
+                ImageSource::relative("bob.png"),
+                "bob",
+                ImageOptions {
+                    add_image_query: Some(false),
+                    remove_margin: true,
+                    align: Some(ImageAlign::Center),
+                    circle_crop: false,
+                },
+            );
+
+        assert_eq!(
+            toast.to_xml(TargetProfile::WINDOWS_11),
+            concat!(
+                r#"<toast><visual addImageQuery="true" baseUri="https://cdn.example.com/">"#,
+                r#"<binding template="ToastGeneric" baseUri="https://cdn.example.com/avatars/">"#,
+                r#"<image id="1" src="alice.png" alt="alice"/>"#,
+                r#"<image id="1" src="bob.png" alt="bob" addImageQuery="false" hint-removeMargin="true" hint-align="center"/>"#,
+                r#"</binding></visual></toast>"#
+            )
+        );
+
+        // the legacy templates know the attributes, but not the hints
+        let xml = toast.to_xml(TargetProfile::WINDOWS_8_1);
+        assert!(xml.contains(
+            r#"<binding template="ToastImageAndText01" baseUri="https://cdn.example.com/avatars/">"#
+        ));
+        assert!(xml.contains(r#"<image id="1" src="bob.png" alt="bob" addImageQuery="false"/>"#));
+    }
+
+    #[test]
+    fn default_image_attributes_are_left_out() {
+        let defaults = toast().image_defaults(ImageDefaults {
+            add_image_query: Some(false),
+            base_uri: Some("ms-appx:///".to_owned()),
+        });
+        assert_eq!(
+            defaults.to_xml(TargetProfile::WINDOWS_11),
+            toast().to_xml(TargetProfile::WINDOWS_11)
+        );
+    }
+
+    #[test]
+    fn custom_audio_needs_fall_creators_update() {
+        let chime = crate::AudioSource::uri("ms-appx:///chime.wav").unwrap();
+        let toast = toast().sound(Some(Sound::Custom(chime.clone())));
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::FALL_CREATORS_UPDATE);
+        assert!(xml.contains(r#"<audio src="ms-appx:///chime.wav"/>"#));
+        assert_eq!(downgrades, []);
+
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::CREATORS_UPDATE);
+        assert!(!xml.contains("<audio"));
+        assert_eq!(downgrades, [Downgrade::CustomAudioReplaced]);
+
+        let toast = toast.sound(Some(Sound::Custom(chime.fallback(Sound::Mail))));
+        let xml = toast.to_xml(TargetProfile::WINDOWS_8_1);
+        assert!(xml.contains(r#"<audio src="ms-winsoundevent:Notification.Mail"/>"#));
+    }
+
+    #[test]
+    fn resolves_looping_audio() {
+        let looping = Some(Sound::Loop(crate::LoopableSound::Alarm2));
+        let xml = toast()
+            .sound(looping.clone())
+            .loop_policy(crate::LoopPolicy::Promote)
+            .to_xml(TargetProfile::WINDOWS_11);
+        assert!(xml.starts_with(r#"<toast duration="long">"#));
+
+        let xml = toast().sound(looping).to_xml(TargetProfile::WINDOWS_11);
+        assert!(xml.starts_with("<toast>"));
+
+        let xml = toast()
+            .scenario(Scenario::Alarm)
+            .to_xml(TargetProfile::WINDOWS_11);
+        assert!(xml
+            .contains(r#"<audio loop="true" src="ms-winsoundevent:Notification.Looping.Alarm"/>"#));
+        let xml = toast()
+            .scenario(Scenario::IncomingCall)
+            .to_xml(TargetProfile::WINDOWS_11);
+        assert!(xml
+            .contains(r#"<audio loop="true" src="ms-winsoundevent:Notification.Looping.Call"/>"#));
+    }
+
+    #[test]
+    fn button_styles_need_windows_11() {
+        let toast = toast().add_styled_button("Delete", "delete", crate::ButtonStyle::Critical);
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_11);
+        assert!(xml.starts_with(r#"<toast useButtonStyle="true">"#));
+        assert!(xml.contains(concat!(
+            r#"<action content='Ok' arguments='ok'/>"#,
+            r#"<action content='Delete' arguments='delete' hint-buttonStyle="Critical"/>"#
+        )));
+        assert_eq!(downgrades, []);
+
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::FALL_CREATORS_UPDATE);
+        assert!(xml.starts_with("<toast>"));
+        assert!(xml.contains(r#"<action content='Delete' arguments='delete'/>"#));
+        assert_eq!(downgrades, [Downgrade::ButtonStylesDropped]);
+    }
+
+    #[test]
+    fn urgent_needs_windows_11_22h2() {
+        let toast = toast().scenario(Scenario::Urgent);
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_11_22H2);
+        assert!(xml.starts_with(r#"<toast scenario="urgent">"#));
+        assert_eq!(downgrades, []);
+
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_11);
+        assert!(xml.starts_with("<toast>"));
+        assert_eq!(downgrades, [Downgrade::UrgentScenarioDropped]);
+    }
+
+    #[test]
+    fn centers_only_call_texts() {
+        let toast = toast().center_call_texts(true);
+        assert!(!toast
+            .to_xml(TargetProfile::WINDOWS_11)
+            .contains("hint-callScenarioCenterAlign"));
+        let xml = toast
+            .scenario(Scenario::IncomingCall)
+            .to_xml(TargetProfile::WINDOWS_11);
+        assert!(xml.contains(r#"<text id="1" hint-callScenarioCenterAlign="true">title</text>"#));
+    }
+
+    #[test]
+    fn launch_and_activation_type() {
+        let toast = toast()
+            .launch("action=open;id=\"1\"")
+            .activation_type(crate::ActivationType::Background);
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_11);
+        assert!(xml.starts_with(
+            r#"<toast launch="action=open;id=&quot;1&quot;" activationType="background">"#
+        ));
+        assert_eq!(downgrades, []);
+
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::WINDOWS_8_1);
+        assert!(xml.starts_with(r#"<toast launch="action=open;id=&quot;1&quot;">"#));
+        assert!(downgrades.contains(&Downgrade::ActivationTypeDropped));
+    }
+
+    #[test]
+    fn protocol_activation() {
+        let store = crate::ProtocolUri::new("ms-windows-store://pdp/?productid=9NBLGGH4NNS1")
+            .unwrap()
+            .target_application("Microsoft.WindowsStore_8wekyb3d8bbwe");
+        let notes = crate::ProtocolUri::new("https://example.com/release notes").unwrap();
+        let toast = toast()
+            .launch_protocol(&notes)
+            .add_protocol_button("Store", &store);
+        let Rendered { xml, downgrades } = toast.render(TargetProfile::CREATORS_UPDATE);
+        assert!(xml.starts_with(
+            r#"<toast launch="https://example.com/release%20notes" activationType="protocol">"#
+        ));
+        assert!(xml.contains(concat!(
+            r#"<action content='Store' arguments='ms-windows-store://pdp/?productid=9NBLGGH4NNS1' "#,
+            r#"activationType="protocol" protocolActivationTargetApplicationPfn="Microsoft.WindowsStore_8wekyb3d8bbwe"/>"#
+        )));
+        assert_eq!(downgrades, []);
+
+        let Rendered { xml, downgrades } = toast
+            .launch_protocol(&store)
+            .render(TargetProfile::ANNIVERSARY_UPDATE);
+        assert!(xml.starts_with(
+            r#"<toast launch="ms-windows-store://pdp/?productid=9NBLGGH4NNS1" activationType="protocol">"#
+        ));
+        assert!(xml.contains(
+            r#"<action content='Store' arguments='ms-windows-store://pdp/?productid=9NBLGGH4NNS1' activationType="protocol"/>"#
+        ));
+        assert_eq!(downgrades, [Downgrade::ProtocolTargetDropped]);
+    }
+
+    #[test]
+    fn escapes_buttons() {
+        let uri = crate::ProtocolUri::new("https://example.com/?a=1&b='2'").unwrap();
+        let xml = toast()
+            .add_button("Ok", "a<b")
+            .add_protocol_button("Open", &uri)
+            .add_button("Don't <Save> & Quit", "quit")
+            .to_xml(TargetProfile::WINDOWS_11);
+        assert!(xml.contains("<action content='Ok' arguments='a&lt;b'/>"));
+        assert!(xml.contains(
+            "<action content='Open' arguments='https://example.com/?a=1&amp;b=&apos;2&apos;'"
+        ));
+        assert!(
+            xml.contains("<action content='Don&apos;t &lt;Save&gt; &amp; Quit' arguments='quit'/>")
+        );
+    }
+}
@@ -0,0 +1,511 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The sounds a toast plays, and their textual form.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{AudioSource, Duration, Scenario};
+
+/// The prefix of the `ms-winsoundevent:` URIs of the built-in sounds.
+const EVENT_PREFIX: &str = "ms-winsoundevent:Notification.";
+
+/// The prefix of [Sound::Loop] in the textual form.
+const LOOP_PREFIX: &str = "loop:";
+
+/// The sound a toast plays.
+///
+/// The [Display] and [FromStr] implementations round-trip every sound:
+///
+/// * `Default`, `IM`, `Mail`, `Reminder` and `SMS`
+/// * `Alarm2` for [Sound::Single] and `loop:Alarm2` for [Sound::Loop]
+/// * the URI of a [custom sound](Sound::Custom), followed by `|` and its fallback if it has one,
+///   as in `ms-appx:///chime.wav|loop:Call`
+///
+/// Parsing also accepts `ms-winsoundevent:` URIs, like `ms-winsoundevent:Notification.Looping.Alarm2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sound {
+    Default,
+    IM,
+    Mail,
+    Reminder,
+    SMS,
+    /// Play the loopable sound only once
+    Single(LoopableSound),
+    /// Loop the loopable sound for the entire duration of the toast
+    Loop(LoopableSound),
+    /// Play a sound file, see [AudioSource]
+    Custom(AudioSource),
+}
+
+impl Sound {
+    /// The sounds that are neither loopable nor custom.
+    const NAMED: [(&'static str, Sound); 5] = [
+        ("Default", Sound::Default),
+        ("IM", Sound::IM),
+        ("Mail", Sound::Mail),
+        ("Reminder", Sound::Reminder),
+        ("SMS", Sound::SMS),
+    ];
+
+    fn named(name: &str) -> Option<Sound> {
+        Sound::NAMED
+            .into_iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, sound)| sound)
+    }
+}
+
+impl TryFrom<&str> for Sound {
+    type Error = SoundParsingError;
+
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        Self::from_str(value)
+    }
+}
+
+impl FromStr for Sound {
+    type Err = SoundParsingError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Some(sound) = s.strip_prefix(LOOP_PREFIX) {
+            return loopable(sound)
+                .map(Sound::Loop)
+                .ok_or_else(|| SoundParsingError::new(s, Expected::Loopable));
+        }
+        if let Some(sound) = Sound::named(s) {
+            return Ok(sound);
+        }
+        if let Some(sound) = loopable(s) {
+            return Ok(Sound::Single(sound));
+        }
+        if let Some(sound) = s.strip_prefix(EVENT_PREFIX).and_then(Sound::named) {
+            return Ok(sound);
+        }
+
+        let (uri, fallback) = match s.split_once('|') {
+            Some((uri, fallback)) => (uri, Some(fallback)),
+            None => (s, None),
+        };
+        let source = match uri.parse::<AudioSource>() {
+            Ok(source) => source,
+            Err(crate::Error::InvalidUri { reason, .. }) if is_file(uri) => {
+                return Err(SoundParsingError::new(s, Expected::File(reason)))
+            }
+            Err(_) => return Err(SoundParsingError::new(s, Expected::Any)),
+        };
+        Ok(Sound::Custom(match fallback {
+            Some(fallback) => source.fallback(fallback.parse()?),
+            None => source,
+        }))
+    }
+}
+
+impl Display for Sound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match &self {
+                Sound::Default => "Default",
+                Sound::IM => "IM",
+                Sound::Mail => "Mail",
+                Sound::Reminder => "Reminder",
+                Sound::SMS => "SMS",
+                Sound::Single(s) => return write!(f, "{s}"),
+                Sound::Loop(s) => return write!(f, "{LOOP_PREFIX}{s}"),
+                Sound::Custom(source) => {
+                    write!(f, "{source}")?;
+                    if let Some(fallback) = source.configured_fallback() {
+                        write!(f, "|{fallback}")?;
+                    }
+                    return Ok(());
+                }
+            }
+        )
+    }
+}
+
+/// What to do with a [Sound::Loop] on a toast that is not on screen long enough to loop,
+/// see [Toast::loop_policy](crate::Toast::loop_policy).
+///
+/// Windows only loops sounds of toasts with [Duration::Long] or the [Scenario::Alarm]
+/// and [Scenario::IncomingCall] scenarios, other toasts play the sound once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopPolicy {
+    /// Show the toast with [Duration::Long].
+    Promote,
+    /// Refuse to show the toast, which fails with [Error::Violations](crate::Error::Violations).
+    Reject,
+    /// Show the toast anyway, [Toast::validate](crate::Toast::validate) reports
+    /// [Violation::LoopNeedsLongDuration](crate::Violation::LoopNeedsLongDuration).
+    #[default]
+    Warn,
+}
+
+/// The sound and duration a toast is shown with.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Resolved {
+    pub sound: Option<Sound>,
+    pub duration: Option<Duration>,
+    /// The sound loops, but the toast is not on screen long enough.
+    pub loop_too_short: bool,
+}
+
+/// Settle how `sound`, `duration` and `scenario` play together under `policy`.
+///
+/// Alarms and incoming calls that play the default sound loop the matching alarm or ringtone.
+pub(crate) fn resolve(
+    sound: Option<&Sound>,
+    duration: Option<Duration>,
+    scenario: Scenario,
+    policy: LoopPolicy,
+) -> Resolved {
+    let sound = match (sound, scenario) {
+        (Some(Sound::Default), Scenario::Alarm) => Some(Sound::Loop(LoopableSound::Alarm)),
+        (Some(Sound::Default), Scenario::IncomingCall) => Some(Sound::Loop(LoopableSound::Call)),
+        (sound, _) => sound.cloned(),
+    };
+    let long_lived = duration == Some(Duration::Long)
+        || matches!(scenario, Scenario::Alarm | Scenario::IncomingCall);
+    if !matches!(sound, Some(Sound::Loop(_))) || long_lived {
+        return Resolved {
+            sound,
+            duration,
+            loop_too_short: false,
+        };
+    }
+    match policy {
+        LoopPolicy::Promote => Resolved {
+            sound,
+            duration: Some(Duration::Long),
+            loop_too_short: false,
+        },
+        LoopPolicy::Reject | LoopPolicy::Warn => Resolved {
+            sound,
+            duration,
+            loop_too_short: true,
+        },
+    }
+}
+
+/// Sounds suitable for Looping
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopableSound {
+    Alarm,
+    Alarm2,
+    Alarm3,
+    Alarm4,
+    Alarm5,
+    Alarm6,
+    Alarm7,
+    Alarm8,
+    Alarm9,
+    Alarm10,
+    Call,
+    Call2,
+    Call3,
+    Call4,
+    Call5,
+    Call6,
+    Call7,
+    Call8,
+    Call9,
+    Call10,
+}
+
+impl LoopableSound {
+    /// Every loopable sound.
+    pub const ALL: [LoopableSound; 20] = [
+        LoopableSound::Alarm,
+        LoopableSound::Alarm2,
+        LoopableSound::Alarm3,
+        LoopableSound::Alarm4,
+        LoopableSound::Alarm5,
+        LoopableSound::Alarm6,
+        LoopableSound::Alarm7,
+        LoopableSound::Alarm8,
+        LoopableSound::Alarm9,
+        LoopableSound::Alarm10,
+        LoopableSound::Call,
+        LoopableSound::Call2,
+        LoopableSound::Call3,
+        LoopableSound::Call4,
+        LoopableSound::Call5,
+        LoopableSound::Call6,
+        LoopableSound::Call7,
+        LoopableSound::Call8,
+        LoopableSound::Call9,
+        LoopableSound::Call10,
+    ];
+}
+
+/// The loopable sound called `s`, either by name or by its `ms-winsoundevent:` URI.
+fn loopable(s: &str) -> Option<LoopableSound> {
+    let name = s
+        .strip_prefix(EVENT_PREFIX)
+        .map_or(Some(s), |event| event.strip_prefix("Looping."))?;
+    LoopableSound::ALL
+        .into_iter()
+        .find(|sound| sound.to_string() == name)
+}
+
+/// Whether `s` looks like it was meant to be a sound file rather than a built-in sound.
+fn is_file(s: &str) -> bool {
+    !s.starts_with("ms-winsoundevent:") && (s.contains(':') || s.contains('.'))
+}
+
+/// What a [SoundParsingError] expected instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expected {
+    /// Any sound.
+    Any,
+    /// A [LoopableSound].
+    Loopable,
+    /// A sound file, which was rejected for the reason given.
+    File(String),
+}
+
+/// A string is not a [Sound] or [LoopableSound].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoundParsingError {
+    input: String,
+    expected: Expected,
+}
+
+impl SoundParsingError {
+    fn new(input: &str, expected: Expected) -> SoundParsingError {
+        SoundParsingError {
+            input: input.to_owned(),
+            expected,
+        }
+    }
+
+    /// The string that could not be parsed.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl Display for SoundParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let loopable = LoopableSound::ALL.map(|sound| sound.to_string()).join(", ");
+        match &self.expected {
+            Expected::Any => {
+                let named = Sound::NAMED.map(|(name, _)| name).join(", ");
+                write!(
+                    f,
+                    "`{}` is not a sound, expected one of {named}, {loopable}, \
+                     `{LOOP_PREFIX}` followed by a loopable sound, \
+                     an `ms-winsoundevent:` URI or the URI of a sound file",
+                    self.input
+                )
+            }
+            Expected::Loopable => write!(
+                f,
+                "`{}` is not a loopable sound, expected one of {loopable}",
+                self.input
+            ),
+            Expected::File(reason) => write!(f, "`{}` is not a sound: {reason}", self.input),
+        }
+    }
+}
+
+impl std::error::Error for SoundParsingError {}
+
+impl TryFrom<&str> for LoopableSound {
+    type Error = SoundParsingError;
+
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        Self::from_str(value)
+    }
+}
+
+impl FromStr for LoopableSound {
+    type Err = SoundParsingError;
+
+    /// Parses names like `Alarm2` and `ms-winsoundevent:` URIs.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        loopable(s).ok_or_else(|| SoundParsingError::new(s, Expected::Loopable))
+    }
+}
+
+impl Display for LoopableSound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                LoopableSound::Alarm => "Alarm",
+                LoopableSound::Alarm2 => "Alarm2",
+                LoopableSound::Alarm3 => "Alarm3",
+                LoopableSound::Alarm4 => "Alarm4",
+                LoopableSound::Alarm5 => "Alarm5",
+                LoopableSound::Alarm6 => "Alarm6",
+                LoopableSound::Alarm7 => "Alarm7",
+                LoopableSound::Alarm8 => "Alarm8",
+                LoopableSound::Alarm9 => "Alarm9",
+                LoopableSound::Alarm10 => "Alarm10",
+                LoopableSound::Call => "Call",
+                LoopableSound::Call2 => "Call2",
+                LoopableSound::Call3 => "Call3",
+                LoopableSound::Call4 => "Call4",
+                LoopableSound::Call5 => "Call5",
+                LoopableSound::Call6 => "Call6",
+                LoopableSound::Call7 => "Call7",
+                LoopableSound::Call8 => "Call8",
+                LoopableSound::Call9 => "Call9",
+                LoopableSound::Call10 => "Call10",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sounds() -> Vec<Sound> {
+        let chime = AudioSource::uri("ms-appx:///sounds/chime.wav").unwrap();
+        let mut sounds: Vec<Sound> = Sound::NAMED.into_iter().map(|(_, sound)| sound).collect();
+        for sound in LoopableSound::ALL {
+            sounds.push(Sound::Single(sound));
+            sounds.push(Sound::Loop(sound));
+        }
+        sounds.push(Sound::Custom(chime.clone()));
+        sounds.push(Sound::Custom(
+            chime.clone().fallback(Sound::Loop(LoopableSound::Call3)),
+        ));
+        sounds.push(Sound::Custom(
+            chime.clone().fallback(Sound::Custom(
+                AudioSource::uri("file:///C:/sounds/ping%7Cpong.mp3")
+                    .unwrap()
+                    .fallback(Sound::SMS),
+            )),
+        ));
+        sounds
+    }
+
+    #[test]
+    fn loop_decision_table() {
+        use LoopPolicy::{Promote, Reject, Warn};
+        let alarm = Sound::Loop(LoopableSound::Alarm);
+        let call = Sound::Loop(LoopableSound::Call);
+        let looped = Sound::Loop(LoopableSound::Alarm3);
+        let single = Sound::Single(LoopableSound::Alarm3);
+        let long = Some(Duration::Long);
+        let short = Some(Duration::Short);
+
+        #[rustfmt::skip]
+        let table = [
+            // sound, duration, scenario, policy => sound, duration, loop too short
+            (Some(&looped), None, Scenario::Default, Promote, Some(&looped), long, false),
+            (Some(&looped), short, Scenario::Default, Promote, Some(&looped), long, false),
+            (Some(&looped), short, Scenario::Reminder, Promote, Some(&looped), long, false),
+            (Some(&looped), None, Scenario::Default, Reject, Some(&looped), None, true),
+            (Some(&looped), short, Scenario::Default, Warn, Some(&looped), short, true),
+            (Some(&looped), long, Scenario::Default, Reject, Some(&looped), long, false),
+            (Some(&looped), None, Scenario::Alarm, Reject, Some(&looped), None, false),
+            (Some(&looped), short, Scenario::IncomingCall, Promote, Some(&looped), short, false),
+            (Some(&single), short, Scenario::Default, Reject, Some(&single), short, false),
+            (Some(&Sound::Default), None, Scenario::Alarm, Warn, Some(&alarm), None, false),
+            (Some(&Sound::Default), None, Scenario::IncomingCall, Warn, Some(&call), None, false),
+            (Some(&Sound::Default), None, Scenario::Reminder, Warn, Some(&Sound::Default), None, false),
+            (Some(&Sound::Mail), None, Scenario::Alarm, Warn, Some(&Sound::Mail), None, false),
+            (None, None, Scenario::Alarm, Promote, None, None, false),
+        ];
+
+        for (sound, duration, scenario, policy, expected_sound, expected_duration, too_short) in
+            table
+        {
+            assert_eq!(
+                resolve(sound, duration, scenario, policy),
+                Resolved {
+                    sound: expected_sound.cloned(),
+                    duration: expected_duration,
+                    loop_too_short: too_short,
+                },
+                "{sound:?} {duration:?} {scenario:?} {policy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for sound in sounds() {
+            let text = sound.to_string();
+            assert_eq!(text.parse::<Sound>(), Ok(sound), "{text}");
+        }
+        assert_eq!(
+            Sound::Loop(LoopableSound::Alarm2).to_string(),
+            "loop:Alarm2"
+        );
+        assert_eq!(
+            Sound::Custom(
+                AudioSource::uri("ms-appx:///chime.wav")
+                    .unwrap()
+                    .fallback(Sound::Loop(LoopableSound::Call))
+            )
+            .to_string(),
+            "ms-appx:///chime.wav|loop:Call"
+        );
+    }
+
+    #[test]
+    fn parses_event_uris() {
+        assert_eq!(
+            "ms-winsoundevent:Notification.Mail".parse(),
+            Ok(Sound::Mail)
+        );
+        assert_eq!(
+            "ms-winsoundevent:Notification.Looping.Alarm2".parse(),
+            Ok(Sound::Single(LoopableSound::Alarm2))
+        );
+        assert_eq!(
+            "loop:ms-winsoundevent:Notification.Looping.Call10".parse(),
+            Ok(Sound::Loop(LoopableSound::Call10))
+        );
+        assert_eq!(
+            "ms-winsoundevent:Notification.Looping.Call10".parse(),
+            Ok(LoopableSound::Call10)
+        );
+    }
+
+    #[test]
+    fn errors_list_the_choices() {
+        let error = "Alarm11".parse::<Sound>().unwrap_err();
+        assert_eq!(error.input(), "Alarm11");
+        assert_eq!(
+            error.to_string(),
+            "`Alarm11` is not a sound, expected one of Default, IM, Mail, Reminder, SMS, \
+             Alarm, Alarm2, Alarm3, Alarm4, Alarm5, Alarm6, Alarm7, Alarm8, Alarm9, Alarm10, \
+             Call, Call2, Call3, Call4, Call5, Call6, Call7, Call8, Call9, Call10, \
+             `loop:` followed by a loopable sound, an `ms-winsoundevent:` URI or the URI of a sound file"
+        );
+
+        assert_eq!(
+            "loop:Mail".parse::<Sound>().unwrap_err().to_string(),
+            "`loop:Mail` is not a loopable sound, expected one of \
+             Alarm, Alarm2, Alarm3, Alarm4, Alarm5, Alarm6, Alarm7, Alarm8, Alarm9, Alarm10, \
+             Call, Call2, Call3, Call4, Call5, Call6, Call7, Call8, Call9, Call10"
+        );
+        assert_eq!(
+            "ms-appx:///chime.ogg"
+                .parse::<Sound>()
+                .unwrap_err()
+                .to_string(),
+            "`ms-appx:///chime.ogg` is not a sound: sounds must be one of .wav, .mp3, .m4a, .wma"
+        );
+        assert_eq!(
+            "ms-appx:///chime.wav|Alarm11"
+                .parse::<Sound>()
+                .unwrap_err()
+                .input(),
+            "Alarm11"
+        );
+    }
+}
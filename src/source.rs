@@ -0,0 +1,551 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Where images and sounds are loaded from, and how local paths become the `file:` URIs Windows expects.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::{Error, ImageCache, Result, Sound};
+
+/// The URI schemes Windows loads toast images from.
+///
+/// `http` and `https` only work for packaged apps.
+const IMAGE_SCHEMES: [&str; 5] = ["file", "ms-appx", "ms-appdata", "http", "https"];
+
+/// Where an image on a toast is loaded from.
+///
+/// Created from a local path, from a `file://`, `ms-appx:///`, `ms-appdata:///`, `http://` or `https://` URI,
+/// or from the [bytes](Self::bytes) of an image.
+/// Relative paths are resolved against the current directory when the source is created.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::path::Path;
+/// use tauri_winrt_notification::{ImageSource, Toast};
+///
+/// Toast::new(Toast::POWERSHELL_APP_ID)
+///     .image(Path::new(r"\\server\share\photos\sun #1.png"), "the sun")
+///     .image("ms-appx:///assets/moon.png".parse::<ImageSource>().unwrap(), "the moon")
+///     .show()
+///     .expect("notification failed");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageSource {
+    kind: SourceKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SourceKind {
+    Path(PathBuf),
+    Uri(String),
+    /// A percent-encoded relative reference.
+    Relative(String),
+    Bytes(Arc<[u8]>),
+}
+
+impl ImageSource {
+    /// An image from a local path.
+    pub fn path(path: &Path) -> ImageSource {
+        ImageSource {
+            kind: SourceKind::Path(absolute(path)),
+        }
+    }
+
+    /// An image from a URI.
+    ///
+    /// Fails with [Error::InvalidUri] for schemes Windows does not load images from.
+    pub fn uri(uri: &str) -> Result<ImageSource> {
+        let scheme = scheme(uri).ok_or_else(|| Error::InvalidUri {
+            uri: uri.to_owned(),
+            reason: "missing scheme".to_owned(),
+        })?;
+        if !IMAGE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+            return Err(Error::InvalidUri {
+                uri: uri.to_owned(),
+                reason: format!("images cannot be loaded from `{scheme}`"),
+            });
+        }
+        Ok(ImageSource {
+            kind: SourceKind::Uri(uri.to_owned()),
+        })
+    }
+
+    /// An image at `path` relative to the [base URI](crate::ImageDefaults::base_uri) of the toast,
+    /// like `avatars/alice.png`.
+    ///
+    /// Windows resolves it when the toast is shown, so the image is neither read nor downloaded by this crate.
+    pub fn relative(path: &str) -> ImageSource {
+        ImageSource {
+            kind: SourceKind::Relative(encode(&path.replace('\\', "/"))),
+        }
+    }
+
+    /// An image held in memory, like one generated at runtime or embedded with `include_bytes!`.
+    ///
+    /// Windows can only show images from files, so the bytes are written to the [ImageCache]
+    /// of the [Notifier](crate::Notifier) that shows the toast.
+    pub fn bytes(data: &[u8]) -> ImageSource {
+        ImageSource {
+            kind: SourceKind::Bytes(data.into()),
+        }
+    }
+
+    /// The local path of the image, `None` for images loaded from a URI or memory.
+    pub fn as_path(&self) -> Option<&Path> {
+        match &self.kind {
+            SourceKind::Path(path) => Some(path),
+            SourceKind::Uri(_) | SourceKind::Relative(_) | SourceKind::Bytes(_) => None,
+        }
+    }
+
+    /// The image data of an image held in memory.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.kind {
+            SourceKind::Bytes(data) => Some(data),
+            SourceKind::Path(_) | SourceKind::Uri(_) | SourceKind::Relative(_) => None,
+        }
+    }
+
+    /// The URI of an image loaded over `http` or `https`.
+    #[cfg(feature = "remote-images")]
+    pub(crate) fn as_remote_uri(&self) -> Option<&str> {
+        match &self.kind {
+            SourceKind::Uri(uri)
+                if scheme(uri).is_some_and(|scheme| {
+                    scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+                }) =>
+            {
+                Some(uri)
+            }
+            _ => None,
+        }
+    }
+
+    /// The URI Windows loads the image from.
+    ///
+    /// For images held in memory this is their file in the default [ImageCache], which only exists once they were shown.
+    pub fn to_uri(&self) -> String {
+        match &self.kind {
+            SourceKind::Path(path) => file_uri(&path.to_string_lossy()),
+            SourceKind::Uri(uri) | SourceKind::Relative(uri) => uri.clone(),
+            SourceKind::Bytes(data) => {
+                file_uri(&ImageCache::default().path_of(data).to_string_lossy())
+            }
+        }
+    }
+
+    /// Write an image held in memory to `cache`, and return the source of the written file.
+    pub(crate) fn store(&self, cache: &ImageCache) -> Result<ImageSource> {
+        match &self.kind {
+            SourceKind::Bytes(data) => Ok(ImageSource {
+                kind: SourceKind::Path(cache.store(data)?),
+            }),
+            SourceKind::Path(_) | SourceKind::Uri(_) | SourceKind::Relative(_) => Ok(self.clone()),
+        }
+    }
+}
+
+impl<P: AsRef<Path> + ?Sized> From<&P> for ImageSource {
+    /// Paths that are already URIs, like `file:///C:/image.png`, are taken as URIs.
+    fn from(path: &P) -> Self {
+        let path = path.as_ref();
+        path.to_str()
+            .filter(|path| is_uri(path))
+            .and_then(|uri| ImageSource::uri(uri).ok())
+            .unwrap_or_else(|| ImageSource::path(path))
+    }
+}
+
+impl From<PathBuf> for ImageSource {
+    fn from(path: PathBuf) -> Self {
+        (&path).into()
+    }
+}
+
+impl FromStr for ImageSource {
+    type Err = Error;
+
+    /// Parses URIs and local paths.
+    fn from_str(s: &str) -> Result<Self> {
+        if is_uri(s) {
+            ImageSource::uri(s)
+        } else {
+            Ok(ImageSource::path(Path::new(s)))
+        }
+    }
+}
+
+impl Display for ImageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            SourceKind::Path(path) => write!(f, "{}", path.display()),
+            SourceKind::Uri(uri) | SourceKind::Relative(uri) => write!(f, "{uri}"),
+            SourceKind::Bytes(data) => write!(f, "<{} bytes>", data.len()),
+        }
+    }
+}
+
+/// The URI schemes Windows plays custom toast sounds from.
+const AUDIO_SCHEMES: [&str; 3] = ["file", "ms-appx", "ms-appdata"];
+
+/// The audio formats Windows plays on toasts.
+const AUDIO_FORMATS: [&str; 4] = ["wav", "mp3", "m4a", "wma"];
+
+/// A custom sound file played by [Sound::Custom].
+///
+/// Created from a local path or from a `file://`, `ms-appx:///` or `ms-appdata:///` URI
+/// of a `.wav`, `.mp3`, `.m4a` or `.wma` file.
+///
+/// Versions of Windows before the Fall Creators Update only play built-in sounds,
+/// and play the [fallback](Self::fallback) instead.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::path::Path;
+/// use tauri_winrt_notification::{AudioSource, LoopableSound, Sound, Toast};
+///
+/// let chime = AudioSource::path(Path::new(r"C:\Program Files\My App\chime.wav"))
+///     .unwrap()
+///     .fallback(Sound::Single(LoopableSound::Call2));
+/// Toast::new(Toast::POWERSHELL_APP_ID)
+///     .sound(Some(Sound::Custom(chime)))
+///     .show()
+///     .expect("notification failed");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioSource {
+    uri: String,
+    fallback: Option<Box<Sound>>,
+}
+
+impl AudioSource {
+    /// A sound from a local path.
+    ///
+    /// Fails with [Error::InvalidUri] if the file is not in a format Windows plays.
+    pub fn path(path: &Path) -> Result<AudioSource> {
+        let uri = file_uri(&absolute(path).to_string_lossy());
+        check_audio_format(&uri)?;
+        Ok(AudioSource {
+            uri,
+            fallback: None,
+        })
+    }
+
+    /// A sound from a URI.
+    ///
+    /// Fails with [Error::InvalidUri] for schemes Windows does not play sounds from,
+    /// and for files that are not in a format Windows plays.
+    pub fn uri(uri: &str) -> Result<AudioSource> {
+        let scheme = scheme(uri).ok_or_else(|| Error::InvalidUri {
+            uri: uri.to_owned(),
+            reason: "missing scheme".to_owned(),
+        })?;
+        if !AUDIO_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+            return Err(Error::InvalidUri {
+                uri: uri.to_owned(),
+                reason: format!("sounds cannot be played from `{scheme}`"),
+            });
+        }
+        check_audio_format(uri)?;
+        Ok(AudioSource {
+            uri: uri.to_owned(),
+            fallback: None,
+        })
+    }
+
+    /// Play `sound` on versions of Windows that only play built-in sounds, [Sound::Default] if not set.
+    ///
+    /// A custom fallback sound is played as [Sound::Default].
+    pub fn fallback(mut self, sound: Sound) -> AudioSource {
+        self.fallback = Some(Box::new(sound));
+        self
+    }
+
+    /// The URI Windows plays the sound from.
+    pub fn as_uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The fallback as it was set.
+    pub(crate) fn configured_fallback(&self) -> Option<&Sound> {
+        self.fallback.as_deref()
+    }
+
+    /// The built-in sound played instead of this one.
+    pub(crate) fn fallback_sound(&self) -> Sound {
+        match self.fallback.as_deref() {
+            Some(Sound::Custom(_)) | None => Sound::Default,
+            Some(sound) => sound.clone(),
+        }
+    }
+}
+
+impl FromStr for AudioSource {
+    type Err = Error;
+
+    /// Parses URIs and local paths.
+    fn from_str(s: &str) -> Result<Self> {
+        if is_uri(s) {
+            AudioSource::uri(s)
+        } else {
+            AudioSource::path(Path::new(s))
+        }
+    }
+}
+
+impl Display for AudioSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.uri)
+    }
+}
+
+/// Fails unless the path of `uri` ends in one of the [AUDIO_FORMATS].
+fn check_audio_format(uri: &str) -> Result<()> {
+    let path = uri.split(['?', '#']).next().unwrap_or_default();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let extension = name.rsplit_once('.').map(|(_, extension)| extension);
+    match extension {
+        Some(extension) if AUDIO_FORMATS.contains(&extension.to_ascii_lowercase().as_str()) => {
+            Ok(())
+        }
+        _ => Err(Error::InvalidUri {
+            uri: uri.to_owned(),
+            reason: format!(
+                "sounds must be one of {}",
+                AUDIO_FORMATS.map(|format| format!(".{format}")).join(", ")
+            ),
+        }),
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_owned();
+    }
+    match std::env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path.to_owned(),
+    }
+}
+
+/// The scheme of `s` if it starts with one.
+///
+/// Single letters are drive letters, not schemes.
+pub(crate) fn scheme(s: &str) -> Option<&str> {
+    let (scheme, _) = s.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() > 1
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+fn is_uri(s: &str) -> bool {
+    scheme(s).is_some()
+}
+
+/// Convert an absolute path to a `file:` URI, with the semantics of Windows paths.
+///
+/// Handles drive letters, UNC paths and the `\\?\` verbatim prefix,
+/// and percent-encodes everything that is not allowed in a URI path.
+pub(crate) fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = match path.strip_prefix("//?/") {
+        Some(verbatim) => match verbatim.strip_prefix("UNC/") {
+            Some(unc) => format!("//{unc}"),
+            None => verbatim.to_owned(),
+        },
+        None => path,
+    };
+
+    if let Some(unc) = path.strip_prefix("//") {
+        // the server becomes the host
+        let (host, rest) = unc.split_once('/').unwrap_or((unc, ""));
+        return format!("file://{}/{}", encode(host), encode(rest));
+    }
+
+    let path = path.trim_start_matches('/');
+    match path.as_bytes() {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => {
+            format!("file:///{}:{}", *drive as char, encode(&path[2..]))
+        }
+        _ => format!("file:///{}", encode(path)),
+    }
+}
+
+/// Percent-encode a URI path, keeping the `/` separators.
+fn encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'/'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+            | b'@' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drive_paths() {
+        assert_eq!(
+            file_uri(r"C:\absolute\path\to\image.jpeg"),
+            "file:///C:/absolute/path/to/image.jpeg"
+        );
+        assert_eq!(
+            file_uri("c:/this/style/works/too/image.png"),
+            "file:///c:/this/style/works/too/image.png"
+        );
+        assert_eq!(
+            file_uri(r"\\?\C:\verbatim\image.png"),
+            "file:///C:/verbatim/image.png"
+        );
+    }
+
+    #[test]
+    fn unc_paths() {
+        assert_eq!(
+            file_uri(r"\\server\share\image.png"),
+            "file://server/share/image.png"
+        );
+        assert_eq!(
+            file_uri(r"\\?\UNC\server\share\image.png"),
+            "file://server/share/image.png"
+        );
+    }
+
+    #[test]
+    fn percent_encoding() {
+        assert_eq!(
+            file_uri(r"C:\my photos\#1 100%.png"),
+            "file:///C:/my%20photos/%231%20100%25.png"
+        );
+        assert_eq!(
+            file_uri(r"C:\fotos\größe?.png"),
+            "file:///C:/fotos/gr%C3%B6%C3%9Fe%3F.png"
+        );
+        assert_eq!(
+            file_uri("/home/user/a b.png"),
+            "file:///home/user/a%20b.png"
+        );
+    }
+
+    #[test]
+    fn uris() {
+        for uri in [
+            "file:///C:/image.png",
+            "ms-appx:///assets/image.png",
+            "ms-appdata:///local/image.png",
+            "https://example.com/image.png",
+            "HTTP://example.com/image.png",
+        ] {
+            let source: ImageSource = uri.parse().unwrap();
+            assert_eq!(source.to_uri(), uri);
+            assert_eq!(source.as_path(), None);
+        }
+
+        assert!(matches!(
+            "ftp://example.com/image.png".parse::<ImageSource>(),
+            Err(Error::InvalidUri { .. })
+        ));
+        assert!(ImageSource::uri("image.png").is_err());
+    }
+
+    #[test]
+    fn relative_sources() {
+        assert_eq!(
+            ImageSource::relative(r"avatars\alice smith.png").to_uri(),
+            "avatars/alice%20smith.png"
+        );
+        assert_eq!(ImageSource::relative("a.png").as_path(), None);
+    }
+
+    #[test]
+    fn audio_sources() {
+        let source = AudioSource::uri("ms-appx:///sounds/chime.WAV").unwrap();
+        assert_eq!(source.as_uri(), "ms-appx:///sounds/chime.WAV");
+        assert_eq!(source.fallback_sound(), Sound::Default);
+
+        let path = std::env::temp_dir().join("ping pong.mp3");
+        let source: AudioSource = path.to_str().unwrap().parse().unwrap();
+        assert_eq!(source.as_uri(), file_uri(&path.to_string_lossy()));
+        let source = source.fallback(Sound::Mail);
+        assert_eq!(source.fallback_sound(), Sound::Mail);
+        let nested = source.clone().fallback(Sound::Custom(source));
+        assert_eq!(nested.fallback_sound(), Sound::Default);
+    }
+
+    #[test]
+    fn invalid_audio_sources() {
+        let reason = |result: Result<AudioSource>| match result {
+            Err(Error::InvalidUri { reason, .. }) => reason,
+            result => panic!("expected an invalid URI, got {result:?}"),
+        };
+        assert_eq!(
+            reason(AudioSource::uri("ms-appx:///sounds/chime.ogg")),
+            "sounds must be one of .wav, .mp3, .m4a, .wma"
+        );
+        assert_eq!(
+            reason(AudioSource::path(Path::new("chime"))),
+            "sounds must be one of .wav, .mp3, .m4a, .wma"
+        );
+        assert_eq!(
+            reason(AudioSource::uri("https://example.com/chime.wav")),
+            "sounds cannot be played from `https`"
+        );
+    }
+
+    #[test]
+    fn paths_that_are_uris() {
+        let source = ImageSource::from(Path::new("file:///C:/image.png"));
+        assert_eq!(source.to_uri(), "file:///C:/image.png");
+    }
+
+    #[test]
+    fn borrowed_paths() {
+        let path = Path::new("C:/image.png");
+        let expected = ImageSource::from(path);
+        assert_eq!(ImageSource::from(&path), expected);
+        assert_eq!(ImageSource::from(&path.to_path_buf()), expected);
+        assert_eq!(ImageSource::from(path.to_path_buf()), expected);
+        assert_eq!(ImageSource::from("C:/image.png"), expected);
+    }
+
+    #[test]
+    fn relative_paths_are_made_absolute() {
+        let source = ImageSource::from(Path::new("image.png"));
+        let expected = std::env::current_dir().unwrap().join("image.png");
+        assert_eq!(source.as_path(), Some(expected.as_path()));
+        assert_eq!(source.to_uri(), file_uri(&expected.to_string_lossy()));
+
+        // drive letters are not schemes
+        assert!(!is_uri(r"C:\image.png"));
+    }
+}
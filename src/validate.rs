@@ -0,0 +1,446 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The documented limits of toast content, see
+//! <https://learn.microsoft.com/en-us/uwp/schemas/tiles/toastschema/schema-root>
+
+use std::fmt::Display;
+
+use crate::protocol::normalize;
+use crate::{ActivationType, Error, ImageSource, LoopPolicy, Scenario, TargetProfile, Toast};
+
+/// Windows shows at most 5 buttons.
+pub(crate) const MAX_ACTIONS: usize = 5;
+/// Windows shows at most 5 inputs.
+pub(crate) const MAX_INPUTS: usize = 5;
+/// A selection input holds at most 5 items.
+pub(crate) const MAX_SELECTION_ITEMS: usize = 5;
+/// The largest payload in bytes Windows accepts.
+pub(crate) const MAX_PAYLOAD_SIZE: usize = 5 * 1024;
+
+/// How bad a [Violation] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The toast is shown, but not the way it was meant to be.
+    Warning,
+    /// Windows drops the toast or the offending content.
+    Error,
+}
+
+/// A rule a toast breaks, found by [Toast::validate].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// More than 5 buttons.
+    TooManyActions { count: usize },
+    /// More than 5 inputs.
+    TooManyInputs { count: usize },
+    /// A selection input with more than 5 items.
+    TooManySelectionItems { input: String, count: usize },
+    /// A payload larger than 5 KB.
+    PayloadTooLarge { size: usize },
+    /// [Scenario::IncomingCall] without any buttons, which Windows shows as a regular toast.
+    MissingCallActions,
+    /// [Sound::Loop](crate::Sound::Loop) on a toast that does not stay on screen, so the sound only plays once.
+    ///
+    /// Looping requires [Duration::Long](crate::Duration::Long), [Scenario::Alarm] or [Scenario::IncomingCall],
+    /// see [Toast::loop_policy]. An [error](Severity::Error) under [LoopPolicy::Reject], where the toast is not shown,
+    /// a [warning](Severity::Warning) otherwise.
+    LoopNeedsLongDuration { policy: LoopPolicy },
+    /// An [ImageDefaults::base_uri](crate::ImageDefaults::base_uri) that is not an absolute URI
+    /// Windows loads images from.
+    InvalidBaseUri { uri: String, reason: String },
+    /// The toast itself, a button or the header send the same arguments,
+    /// so the [target](crate::Activation::target) of their activations cannot be told apart.
+    ///
+    /// A toast without [launch](Toast::launch) arguments sends empty arguments.
+    AmbiguousArguments { arguments: String },
+    /// [ActivationType::Protocol] with [launch](Toast::launch) arguments that are not a URI
+    /// allowed to be opened from a toast, see [ProtocolUri](crate::ProtocolUri).
+    InvalidProtocolUri { uri: String, reason: String },
+    /// A [tag](Toast::tag) Windows refused when the toast was shown.
+    ///
+    /// Only reported by Windows, never found by [Toast::validate].
+    InvalidTag { tag: String, reason: String },
+}
+
+impl Violation {
+    /// How bad the violation is.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Violation::LoopNeedsLongDuration {
+                policy: LoopPolicy::Reject,
+            } => Severity::Error,
+            Violation::MissingCallActions
+            | Violation::LoopNeedsLongDuration { .. }
+            | Violation::AmbiguousArguments { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::TooManyActions { count } => {
+                write!(f, "{count} buttons, at most {MAX_ACTIONS} are allowed")
+            }
+            Violation::TooManyInputs { count } => {
+                write!(f, "{count} inputs, at most {MAX_INPUTS} are allowed")
+            }
+            Violation::TooManySelectionItems { input, count } => write!(
+                f,
+                "{count} items in selection `{input}`, at most {MAX_SELECTION_ITEMS} are allowed"
+            ),
+            Violation::PayloadTooLarge { size } => write!(
+                f,
+                "the payload is {size} bytes, at most {MAX_PAYLOAD_SIZE} are allowed"
+            ),
+            Violation::MissingCallActions => {
+                write!(f, "incoming call toasts need at least one button")
+            }
+            Violation::LoopNeedsLongDuration { .. } => write!(
+                f,
+                "looping sounds need a long duration or an alarm or incoming call scenario"
+            ),
+            Violation::InvalidBaseUri { uri, reason } => {
+                write!(f, "invalid base URI `{uri}`: {reason}")
+            }
+            Violation::AmbiguousArguments { arguments } => write!(
+                f,
+                "`{arguments}` is sent by more than one part of the toast, so clicks on them cannot be told apart"
+            ),
+            Violation::InvalidProtocolUri { uri, reason } => {
+                write!(f, "invalid protocol URI `{uri}`: {reason}")
+            }
+            Violation::InvalidTag { tag, reason } => write!(f, "invalid tag `{tag}`: {reason}"),
+        }
+    }
+}
+
+/// [Violation::PayloadTooLarge] if the payload `xml` is too large.
+pub(crate) fn payload_size(xml: &str) -> Option<Violation> {
+    (xml.len() > MAX_PAYLOAD_SIZE).then_some(Violation::PayloadTooLarge { size: xml.len() })
+}
+
+pub(crate) fn validate(toast: &Toast, profile: TargetProfile) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    if toast.buttons.len() > MAX_ACTIONS {
+        violations.push(Violation::TooManyActions {
+            count: toast.buttons.len(),
+        });
+    }
+
+    if toast.inputs.len() > MAX_INPUTS {
+        violations.push(Violation::TooManyInputs {
+            count: toast.inputs.len(),
+        });
+    }
+
+    for input in &toast.inputs {
+        if input.item_count() > MAX_SELECTION_ITEMS {
+            violations.push(Violation::TooManySelectionItems {
+                input: input.id().to_owned(),
+                count: input.item_count(),
+            });
+        }
+    }
+
+    violations.extend(payload_size(&toast.to_xml(profile)));
+
+    if toast.scenario == Scenario::IncomingCall && toast.buttons.is_empty() {
+        violations.push(Violation::MissingCallActions);
+    }
+
+    if toast.audio().loop_too_short {
+        violations.push(Violation::LoopNeedsLongDuration {
+            policy: toast.loop_policy,
+        });
+    }
+
+    // URIs are opened by other apps, so only arguments sent to the app itself can clash
+    let protocol_launch = toast.activation_type == Some(ActivationType::Protocol);
+    let launch = (!protocol_launch).then(|| toast.launch.as_deref().unwrap_or_default());
+    let header = toast
+        .header
+        .as_ref()
+        .map(|header| header.arguments.as_str());
+    let mut ambiguous: Vec<&str> = toast
+        .buttons
+        .iter()
+        .filter(|b| b.protocol.is_none())
+        .map(|b| b.action.as_str())
+        .filter(|action| Some(*action) == launch || Some(*action) == header)
+        .chain(header.filter(|header| Some(*header) == launch))
+        .collect();
+    ambiguous.sort_unstable();
+    ambiguous.dedup();
+    for arguments in ambiguous {
+        violations.push(Violation::AmbiguousArguments {
+            arguments: arguments.to_owned(),
+        });
+    }
+
+    if protocol_launch {
+        if let Err(Error::InvalidUri { uri, reason }) =
+            normalize(toast.launch.as_deref().unwrap_or_default())
+        {
+            violations.push(Violation::InvalidProtocolUri { uri, reason });
+        }
+    }
+
+    for defaults in [&toast.visual_images, &toast.binding_images] {
+        if let Some(Err(Error::InvalidUri { uri, reason })) =
+            defaults.base_uri.as_deref().map(ImageSource::uri)
+        {
+            violations.push(Violation::InvalidBaseUri { uri, reason });
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Duration, Input, LoopableSound, Sound};
+
+    fn toast() -> Toast {
+        Toast::new(Toast::POWERSHELL_APP_ID).title("title")
+    }
+
+    #[test]
+    fn valid_toast() {
+        let toast = toast()
+            .text1("line1")
+            .text2("line2")
+            .add_input(Input::text("reply"))
+            .add_button("Send", "send");
+        assert_eq!(toast.validate_for(TargetProfile::WINDOWS_11), []);
+    }
+
+    #[test]
+    fn too_many_actions_and_inputs() {
+        let mut toast = toast();
+        for i in 0..6 {
+            toast = toast
+                .add_button(&format!("button {i}"), &i.to_string())
+                .add_input(Input::text(&i.to_string()));
+        }
+        assert_eq!(
+            toast.validate_for(TargetProfile::WINDOWS_11),
+            [
+                Violation::TooManyActions { count: 6 },
+                Violation::TooManyInputs { count: 6 }
+            ]
+        );
+    }
+
+    #[test]
+    fn too_many_selection_items() {
+        let items = [("1", "1"), ("2", "2"), ("3", "3"), ("4", "4"), ("5", "5")];
+        let toast = toast()
+            .add_input(Input::selection("ok", &items))
+            .add_input(Input::selection(
+                "too_many",
+                &[&items[..], &[("6", "6")]].concat(),
+            ));
+        assert_eq!(
+            toast.validate_for(TargetProfile::WINDOWS_11),
+            [Violation::TooManySelectionItems {
+                input: "too_many".to_owned(),
+                count: 6
+            }]
+        );
+    }
+
+    #[test]
+    fn payload_too_large() {
+        let toast = toast().text1(&"a".repeat(MAX_PAYLOAD_SIZE));
+        let violations = toast.validate_for(TargetProfile::WINDOWS_11);
+        assert!(matches!(
+            violations[..],
+            [Violation::PayloadTooLarge { size }] if size > MAX_PAYLOAD_SIZE
+        ));
+        assert_eq!(violations[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn incoming_call_needs_buttons() {
+        let toast = toast().scenario(Scenario::IncomingCall);
+        assert_eq!(
+            toast.validate_for(TargetProfile::WINDOWS_11),
+            [Violation::MissingCallActions]
+        );
+        assert_eq!(
+            toast
+                .add_button("Answer", "answer")
+                .validate_for(TargetProfile::WINDOWS_11),
+            Vec::<Violation>::new()
+        );
+    }
+
+    #[test]
+    fn looping_sound_needs_long_toast() {
+        let looping = Some(Sound::Loop(LoopableSound::Alarm));
+
+        let violations = toast()
+            .sound(looping.clone())
+            .validate_for(TargetProfile::WINDOWS_11);
+        assert_eq!(
+            violations,
+            [Violation::LoopNeedsLongDuration {
+                policy: LoopPolicy::Warn
+            }]
+        );
+        assert_eq!(violations[0].severity(), Severity::Warning);
+
+        assert_eq!(
+            toast()
+                .sound(looping.clone())
+                .duration(Duration::Short)
+                .validate_for(TargetProfile::WINDOWS_11),
+            [Violation::LoopNeedsLongDuration {
+                policy: LoopPolicy::Warn
+            }]
+        );
+        assert_eq!(
+            toast()
+                .sound(looping.clone())
+                .duration(Duration::Long)
+                .validate_for(TargetProfile::WINDOWS_11),
+            []
+        );
+        assert_eq!(
+            toast()
+                .sound(looping.clone())
+                .scenario(Scenario::Alarm)
+                .add_button("Stop", "stop")
+                .validate_for(TargetProfile::WINDOWS_11),
+            []
+        );
+        assert_eq!(
+            toast()
+                .sound(Some(Sound::Single(LoopableSound::Alarm)))
+                .validate_for(TargetProfile::WINDOWS_11),
+            []
+        );
+
+        let violations = toast()
+            .sound(looping)
+            .loop_policy(LoopPolicy::Reject)
+            .validate_for(TargetProfile::WINDOWS_11);
+        assert_eq!(
+            violations,
+            [Violation::LoopNeedsLongDuration {
+                policy: LoopPolicy::Reject
+            }]
+        );
+        assert_eq!(violations[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn base_uris_must_be_absolute() {
+        let defaults = |uri: &str| crate::ImageDefaults {
+            base_uri: Some(uri.to_owned()),
+            ..Default::default()
+        };
+        let toast = toast()
+            .image_defaults(defaults("https://cdn.example.com/avatars/"))
+            .binding_image_defaults(defaults("avatars/"));
+        assert_eq!(
+            toast.validate_for(TargetProfile::WINDOWS_11),
+            [Violation::InvalidBaseUri {
+                uri: "avatars/".to_owned(),
+                reason: "missing scheme".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn arguments_must_tell_targets_apart() {
+        let toast = toast().add_button("Open", "open");
+        assert_eq!(
+            toast
+                .clone()
+                .launch("show")
+                .validate_for(TargetProfile::WINDOWS_11),
+            []
+        );
+        assert_eq!(
+            toast
+                .clone()
+                .launch("open")
+                .validate_for(TargetProfile::WINDOWS_11),
+            [Violation::AmbiguousArguments {
+                arguments: "open".to_owned()
+            }]
+        );
+
+        let header = crate::Header {
+            id: "id".to_owned(),
+            title: "title".to_owned(),
+            arguments: String::new(),
+        };
+        let violations = toast
+            .add_button("Empty", "")
+            .header(&header)
+            .validate_for(TargetProfile::WINDOWS_11);
+        assert_eq!(
+            violations,
+            [Violation::AmbiguousArguments {
+                arguments: String::new()
+            }]
+        );
+        assert_eq!(violations[0].severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn protocol_activations_are_not_ambiguous() {
+        let uri = crate::ProtocolUri::new("https://example.com").unwrap();
+        let buttons = toast()
+            .launch("https://example.com")
+            .add_protocol_button("Open", &uri);
+        assert_eq!(buttons.validate_for(TargetProfile::WINDOWS_11), []);
+        assert_eq!(
+            toast()
+                .launch_protocol(&uri)
+                .add_button("Empty", "")
+                .validate_for(TargetProfile::WINDOWS_11),
+            []
+        );
+    }
+
+    #[test]
+    fn protocol_launch_needs_a_uri() {
+        let toast = toast().activation_type(ActivationType::Protocol);
+        let violations = toast.clone().validate_for(TargetProfile::WINDOWS_11);
+        assert_eq!(
+            violations,
+            [Violation::InvalidProtocolUri {
+                uri: String::new(),
+                reason: "missing scheme".to_owned()
+            }]
+        );
+        assert_eq!(violations[0].severity(), Severity::Error);
+        assert_eq!(
+            toast
+                .clone()
+                .launch("javascript:alert(1)")
+                .validate_for(TargetProfile::WINDOWS_11),
+            [Violation::InvalidProtocolUri {
+                uri: "javascript:alert(1)".to_owned(),
+                reason: "`javascript` URIs cannot be opened from toasts".to_owned()
+            }]
+        );
+        assert_eq!(
+            toast
+                .launch("myapp://chat/42")
+                .validate_for(TargetProfile::WINDOWS_11),
+            []
+        );
+    }
+}
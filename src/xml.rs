@@ -0,0 +1,182 @@
+// Copyright 2017-2022 allenbenz <allenbenz@users.noreply.github.com>
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Well-formedness checks for toast xml.
+//!
+//! `XmlDocument::LoadXml` only reports an HRESULT, so the xml is checked here first
+//! to be able to point at what is wrong with it.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::{Error, Result};
+
+/// Snippets longer than this are cut off.
+const MAX_SNIPPET_LEN: usize = 80;
+
+/// Check that `xml` is well-formed.
+///
+/// `xml` can be a whole toast payload or a fragment of several elements.
+pub(crate) fn check(xml: &str) -> Result<()> {
+    let mut reader = Reader::from_str(xml);
+    reader.check_end_names(true);
+
+    // the names and positions of the open elements
+    let mut open = Vec::new();
+    loop {
+        let position = reader.buffer_position();
+        let invalid = |message: String| invalid_xml(xml, position, message);
+
+        match reader.read_event() {
+            Ok(Event::Start(element)) => {
+                for attribute in element.attributes() {
+                    attribute
+                        .map_err(|e| invalid(e.to_string()))?
+                        .unescape_value()
+                        .map_err(|e| invalid(e.to_string()))?;
+                }
+                open.push((element.name().as_ref().to_vec(), position));
+            }
+            Ok(Event::Empty(element)) => {
+                for attribute in element.attributes() {
+                    attribute
+                        .map_err(|e| invalid(e.to_string()))?
+                        .unescape_value()
+                        .map_err(|e| invalid(e.to_string()))?;
+                }
+            }
+            Ok(Event::End(_)) => {
+                open.pop();
+            }
+            Ok(Event::Text(text)) => {
+                text.unescape().map_err(|e| invalid(e.to_string()))?;
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(invalid(e.to_string())),
+        }
+    }
+
+    match open.pop() {
+        Some((name, position)) => Err(invalid_xml(
+            xml,
+            position,
+            format!("`{}` is never closed", String::from_utf8_lossy(&name)),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn invalid_xml(xml: &str, position: usize, message: String) -> Error {
+    let position = position.min(xml.len());
+    let before = &xml[..position];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line_end = xml[position..]
+        .find('\n')
+        .map_or(xml.len(), |i| position + i);
+
+    let column = before[line_start..].chars().count();
+
+    Error::InvalidXml {
+        message,
+        line: before.matches('\n').count() + 1,
+        column: column + 1,
+        snippet: snippet(&xml[line_start..line_end], column),
+    }
+}
+
+/// The part of `line` around the char at `column`, at most [MAX_SNIPPET_LEN] chars long.
+fn snippet(line: &str, column: usize) -> String {
+    let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+    let chars: Vec<char> = line.trim().chars().collect();
+    if chars.len() <= MAX_SNIPPET_LEN {
+        return chars.into_iter().collect();
+    }
+
+    // keep a bit of context before the error
+    let start = column
+        .saturating_sub(indent + MAX_SNIPPET_LEN / 4)
+        .min(chars.len() - MAX_SNIPPET_LEN);
+    let end = start + MAX_SNIPPET_LEN;
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(&chars[start..end]);
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(xml: &str) -> (usize, usize, String) {
+        match check(xml) {
+            Err(Error::InvalidXml {
+                line,
+                column,
+                snippet,
+                ..
+            }) => (line, column, snippet),
+            result => panic!("expected invalid xml, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_well_formed_xml() {
+        check(r#"<toast><visual><binding template="ToastGeneric"><text id="1">a &amp; b</text></binding></visual></toast>"#).unwrap();
+        // fragments
+        check(r#"<text>one</text><text>two</text>"#).unwrap();
+        check("").unwrap();
+    }
+
+    #[test]
+    fn rejects_mismatched_end() {
+        let xml = "<toast>\n  <visual>\n  </binding>\n</toast>";
+        assert_eq!(position(xml), (3, 3, "</binding>".to_owned()));
+    }
+
+    #[test]
+    fn rejects_unclosed_element() {
+        assert_eq!(
+            position("<toast>\n  <visual>"),
+            (2, 3, "<visual>".to_owned())
+        );
+    }
+
+    #[test]
+    fn rejects_broken_attributes() {
+        // an unescaped quote, like in a button label
+        let xml = "<actions>\n<action content='Don't' arguments='no'/>\n</actions>";
+        let (line, column, snippet) = position(xml);
+        assert_eq!((line, column), (2, 1));
+        assert_eq!(snippet, "<action content='Don't' arguments='no'/>");
+    }
+
+    #[test]
+    fn rejects_unknown_entities() {
+        let xml = r#"<text id="1">fish &chips</text>"#;
+        assert_eq!(position(xml).0, 1);
+    }
+
+    #[test]
+    fn truncates_long_snippets() {
+        let xml = format!("<text>{}</txt>", "a".repeat(200));
+        let (_, _, snippet) = position(&xml);
+        assert_eq!(snippet.chars().count(), MAX_SNIPPET_LEN + 1);
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with("a</txt>"));
+
+        let xml = format!("<a><b x='it's'/>{}</a>", "<c/>".repeat(50));
+        let (_, _, snippet) = position(&xml);
+        assert_eq!(snippet.chars().count(), MAX_SNIPPET_LEN + 1);
+        assert!(snippet.starts_with("<a><b x='it's'/>"));
+        assert!(snippet.ends_with('…'));
+    }
+}